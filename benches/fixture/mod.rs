@@ -0,0 +1,49 @@
+//! Generates synthetic python projects to benchmark against.
+use std::fs;
+use std::path::Path;
+
+use tempfile::TempDir;
+
+const MODULES_PER_PACKAGE: usize = 50;
+
+/// Writes a python project of `num_files` modules spread over packages of
+/// `MODULES_PER_PACKAGE` modules each.
+///
+/// Every module imports the next one in its package, except for every tenth module which nothing
+/// imports, so that roughly 10% of the project is dead.
+pub fn generate_project(num_files: usize) -> TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    fs::write(root.join("pyproject.toml"), "").unwrap();
+
+    let mut entrypoint = String::new();
+    for i in 0..num_files {
+        let package = format!("pkg_{}", i / MODULES_PER_PACKAGE);
+        let package_dir = root.join(&package);
+        if i.is_multiple_of(MODULES_PER_PACKAGE) {
+            fs::create_dir_all(&package_dir).unwrap();
+            fs::write(package_dir.join("__init__.py"), "").unwrap();
+            entrypoint.push_str(&format!("import {package}.mod_{i}\n"));
+        }
+        write_module(&package_dir, &package, i, num_files);
+    }
+    entrypoint.push_str("\nif __name__ == \"__main__\":\n    pass\n");
+    fs::write(root.join("main.py"), entrypoint).unwrap();
+    dir
+}
+
+fn write_module(package_dir: &Path, package: &str, i: usize, num_files: usize) {
+    let mut contents = String::from("import os\nfrom typing import Any\n");
+    let next = i + 1;
+    let same_package = !next.is_multiple_of(MODULES_PER_PACKAGE);
+    if next < num_files && same_package && !next.is_multiple_of(10) {
+        contents.push_str(&format!("from {package} import mod_{next}\n"));
+        contents.push_str(&format!(
+            "from .mod_{next} import helper as helper_{next}\n"
+        ));
+    }
+    contents.push_str(&format!(
+        "\n\ndef helper(value: Any) -> Any:\n    return os.path.join(str(value), \"{i}\")\n"
+    ));
+    fs::write(package_dir.join(format!("mod_{i}.py")), contents).unwrap();
+}
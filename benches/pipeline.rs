@@ -0,0 +1,47 @@
+use std::path::{Path, PathBuf};
+
+use clap::Parser;
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use undead::cli::Cli;
+use undead::{extract_imports, find_dead_files, render_as_import_string, ImportOptions};
+
+mod fixture;
+
+fn bench_find_dead_files(c: &mut Criterion) {
+    let mut group = c.benchmark_group("find_dead_files");
+    group.sample_size(20);
+    for num_files in [100, 1_000, 5_000] {
+        let project = fixture::generate_project(num_files);
+        let cli = Cli::parse_from(["undead", project.path().to_str().unwrap()]);
+        group.bench_with_input(BenchmarkId::from_parameter(num_files), &cli, |b, cli| {
+            b.iter(|| find_dead_files(black_box(cli)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn bench_extract_imports(c: &mut Criterion) {
+    let project = fixture::generate_project(2);
+    let root = project.path().canonicalize().unwrap();
+    let module = root.join("pkg_0").join("mod_0.py");
+    let options = ImportOptions::default();
+    c.bench_function("extract_imports", |b| {
+        b.iter(|| extract_imports(black_box(&module), black_box(&root), &options).unwrap())
+    });
+}
+
+fn bench_render_as_import_string(c: &mut Criterion) {
+    let root = PathBuf::from("/home/user/project");
+    let path = root.join("pkg").join("sub").join("module.py");
+    c.bench_function("render_as_import_string", |b| {
+        b.iter(|| render_as_import_string(black_box(&path), black_box(Path::new(&root))))
+    });
+}
+
+criterion_group!(
+    benches,
+    bench_find_dead_files,
+    bench_extract_imports,
+    bench_render_as_import_string
+);
+criterion_main!(benches);
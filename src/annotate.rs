@@ -0,0 +1,99 @@
+//! Marking of dead files with a comment, so that reviewers notice them in their editor.
+use std::path::Path;
+
+/// Comment prepended to dead files.
+pub const MARKER: &str = "# undead: dead";
+
+/// Prepends `MARKER` to the file at `path`, telling whether it wasn't there already.
+pub fn annotate_file(path: &Path) -> std::io::Result<bool> {
+    let contents = std::fs::read_to_string(path)?;
+    match annotated(&contents) {
+        Some(annotated) => {
+            std::fs::write(path, annotated)?;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// `contents` with `MARKER` inserted at the top, or `None` when it already has it.
+///
+/// The marker goes after any shebang and encoding declaration, which must stay on the first lines.
+fn annotated(contents: &str) -> Option<String> {
+    let lines: Vec<&str> = contents.split_inclusive('\n').collect();
+    let header_len = lines
+        .iter()
+        .take(2)
+        .enumerate()
+        .take_while(|(index, line)| {
+            (*index == 0 && line.starts_with("#!")) || is_encoding_declaration(line)
+        })
+        .count();
+    if lines
+        .iter()
+        .take(header_len + 1)
+        .any(|line| line.trim() == MARKER)
+    {
+        return None;
+    }
+    let newline = if contents.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+    let mut annotated = String::with_capacity(contents.len() + MARKER.len() + newline.len());
+    for line in &lines[..header_len] {
+        annotated.push_str(line);
+    }
+    if header_len > 0 && !annotated.ends_with('\n') {
+        annotated.push_str(newline);
+    }
+    annotated.push_str(MARKER);
+    annotated.push_str(newline);
+    for line in &lines[header_len..] {
+        annotated.push_str(line);
+    }
+    Some(annotated)
+}
+
+/// Tells whether `line` is a `# -*- coding: ... -*-` declaration, as PEP 263 describes.
+fn is_encoding_declaration(line: &str) -> bool {
+    line.trim_start().starts_with('#') && (line.contains("coding:") || line.contains("coding="))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_annotate_file_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("dead.py");
+        std::fs::write(&path, "import os\n").unwrap();
+
+        assert!(annotate_file(&path).unwrap());
+        assert!(!annotate_file(&path).unwrap());
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "# undead: dead\nimport os\n"
+        );
+    }
+
+    #[test]
+    fn test_annotated_keeps_header_lines_first() {
+        assert_eq!(
+            annotated("#!/usr/bin/env python\n# -*- coding: utf-8 -*-\nx = 1\n").unwrap(),
+            "#!/usr/bin/env python\n# -*- coding: utf-8 -*-\n# undead: dead\nx = 1\n"
+        );
+        assert_eq!(
+            annotated("#!/usr/bin/env python").unwrap(),
+            "#!/usr/bin/env python\n# undead: dead\n"
+        );
+        assert_eq!(annotated("").unwrap(), "# undead: dead\n");
+        assert_eq!(
+            annotated("x = 1\r\n").unwrap(),
+            "# undead: dead\r\nx = 1\r\n"
+        );
+        assert!(annotated("#!/usr/bin/env python\n# undead: dead\n").is_none());
+    }
+}
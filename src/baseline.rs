@@ -0,0 +1,53 @@
+//! Reading and writing of baselines, the dead files already known of and not to report again.
+use std::io::Write;
+use std::path::Path;
+
+use crate::{diff, DeadFile};
+
+/// Reads the entries of the baseline at `path`, either a JSON report (as written by
+/// `--format json`) or a list of relative paths or dotted modules, one per line, `#` starting
+/// comments.
+pub(crate) fn read_baseline(path: &Path) -> anyhow::Result<Vec<String>> {
+    let contents = std::fs::read_to_string(path)?;
+    if contents.trim_start().starts_with('{') {
+        let report = diff::read_report(path)?;
+        return Ok(report
+            .dead_files
+            .into_iter()
+            .map(|dead_file| dead_file.repr)
+            .collect());
+    }
+    Ok(contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect())
+}
+
+/// Writes `dead_files` to `path` as a baseline listing their relative paths.
+pub fn write_baseline(path: &Path, dead_files: &[DeadFile]) -> std::io::Result<()> {
+    let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+    for dead_file in dead_files {
+        writeln!(file, "{}", dead_file.repr)?;
+    }
+    file.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_baseline_round_trips() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("baseline.txt");
+        let dead_files = crate::Report::for_test(dir.path(), &["a.py", "pkg/b.py"], 2).dead_files;
+
+        write_baseline(&path, &dead_files).unwrap();
+        assert_eq!(read_baseline(&path).unwrap(), vec!["a.py", "pkg/b.py"]);
+
+        std::fs::write(&path, "# known dead\npkg.c\n\nd.py\n").unwrap();
+        assert_eq!(read_baseline(&path).unwrap(), vec!["pkg.c", "d.py"]);
+    }
+}
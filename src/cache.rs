@@ -0,0 +1,182 @@
+//! On-disk cache of the imports extracted from each file, so that unchanged files aren't parsed
+//! again on subsequent runs.
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{FileAnalysis, ImportOptions};
+
+static CACHE_DIR_NAME: &str = ".undead_cache";
+
+/// Picks where to store the cache when no `--cache-dir` is given: under `$XDG_CACHE_HOME` when
+/// set, in the project root otherwise.
+pub(crate) fn default_cache_dir(python_root: &Path, xdg_cache_home: Option<PathBuf>) -> PathBuf {
+    match xdg_cache_home {
+        Some(xdg_cache_home) if !xdg_cache_home.as_os_str().is_empty() => {
+            xdg_cache_home.join("undead")
+        }
+        _ => python_root.join(CACHE_DIR_NAME),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct CacheEntry {
+    modified_nanos: u128,
+    size: u64,
+    /// stamps of the package inits star imports were expanded from, missing ones having none
+    star_import_stamps: Vec<(PathBuf, Option<(u128, u64)>)>,
+    analysis: FileAnalysis,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct ImportCache {
+    /// identifies the set of files and options the entries were computed for, since imports get
+    /// resolved against the rest of the project
+    fingerprint: u64,
+    entries: HashMap<PathBuf, CacheEntry>,
+}
+
+impl ImportCache {
+    /// Computes the fingerprint entries must match for the given project files and options.
+    pub(crate) fn fingerprint(python_files: &[PathBuf], options: &ImportOptions) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        env!("CARGO_PKG_VERSION").hash(&mut hasher);
+        python_files.hash(&mut hasher);
+        options.relative_import_base.hash(&mut hasher);
+        options.namespace_roots.hash(&mut hasher);
+        options.parse_mode.hash(&mut hasher);
+        options.ignore_type_checking_imports.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Path of the cache file for the project at `python_root` inside `cache_dir`.
+    pub(crate) fn file_path(cache_dir: &Path, python_root: &Path) -> PathBuf {
+        let mut hasher = DefaultHasher::new();
+        python_root.hash(&mut hasher);
+        cache_dir.join(format!("imports-{:016x}.json", hasher.finish()))
+    }
+
+    /// Loads the cache at `path`, starting afresh if it is missing, unreadable or stale.
+    pub(crate) fn load(path: &Path, fingerprint: u64) -> ImportCache {
+        let cache = fs::read_to_string(path)
+            .ok()
+            .and_then(|contents| serde_json::from_str::<ImportCache>(&contents).ok());
+        match cache {
+            Some(cache) if cache.fingerprint == fingerprint => cache,
+            _ => ImportCache {
+                fingerprint,
+                entries: HashMap::new(),
+            },
+        }
+    }
+
+    pub(crate) fn save(&self, path: &Path) -> anyhow::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Returns the cached analysis of `path` if neither the file nor the package inits its star
+    /// imports were expanded from changed since it was cached.
+    pub(crate) fn get(&self, path: &Path) -> Option<&FileAnalysis> {
+        let entry = self.entries.get(path)?;
+        let (modified_nanos, size) = file_stamp(path)?;
+        if entry.modified_nanos == modified_nanos
+            && entry.size == size
+            && entry
+                .star_import_stamps
+                .iter()
+                .all(|(init, stamp)| file_stamp(init) == *stamp)
+        {
+            Some(&entry.analysis)
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn insert(&mut self, path: PathBuf, analysis: FileAnalysis) {
+        if let Some((modified_nanos, size)) = file_stamp(&path) {
+            let star_import_stamps = analysis
+                .star_import_inits
+                .iter()
+                .map(|init| (init.clone(), file_stamp(init)))
+                .collect();
+            self.entries.insert(
+                path,
+                CacheEntry {
+                    modified_nanos,
+                    size,
+                    star_import_stamps,
+                    analysis,
+                },
+            );
+        }
+    }
+}
+
+fn file_stamp(path: &Path) -> Option<(u128, u64)> {
+    let metadata = fs::metadata(path).ok()?;
+    let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+    Some((modified.as_nanos(), metadata.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Import;
+
+    #[test]
+    fn test_default_cache_dir() {
+        let root = Path::new("/project");
+        assert_eq!(
+            default_cache_dir(root, None),
+            PathBuf::from("/project/.undead_cache")
+        );
+        assert_eq!(
+            default_cache_dir(root, Some(PathBuf::new())),
+            PathBuf::from("/project/.undead_cache")
+        );
+        assert_eq!(
+            default_cache_dir(root, Some(PathBuf::from("/home/user/.cache"))),
+            PathBuf::from("/home/user/.cache/undead")
+        );
+    }
+
+    #[test]
+    fn test_cache_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let python_file = dir.path().join("a.py");
+        fs::write(&python_file, "import b\n").unwrap();
+        let cache_path = ImportCache::file_path(&dir.path().join("cache"), dir.path());
+
+        let mut cache = ImportCache::load(&cache_path, 42);
+        assert_eq!(cache.entries.len(), 0);
+        let analysis = FileAnalysis {
+            imports: vec![Import::Module("b".to_string())],
+            dynamic_import: None,
+            dynamic_all: false,
+            warnings: vec![],
+            unresolved: vec![],
+            entrypoint_markers: Default::default(),
+            content_hash: None,
+            star_import_inits: vec![],
+        };
+        cache.insert(python_file.clone(), analysis.clone());
+        cache.save(&cache_path).unwrap();
+
+        let cache = ImportCache::load(&cache_path, 42);
+        assert_eq!(cache.get(&python_file), Some(&analysis));
+        // a different fingerprint invalidates everything
+        assert_eq!(ImportCache::load(&cache_path, 43).entries.len(), 0);
+
+        fs::write(&python_file, "import b, c\n").unwrap();
+        assert_eq!(cache.get(&python_file), None);
+    }
+}
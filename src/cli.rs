@@ -1,14 +1,368 @@
+use std::num::NonZeroUsize;
 use std::path::PathBuf;
 
-use clap::Parser;
+use clap::parser::ValueSource;
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
+
+use crate::{Confidence, ParseMode};
 
 #[derive(Parser, Debug)]
-#[command(arg_required_else_help=true, version, about, long_about = None)]
+#[command(arg_required_else_help=true, args_conflicts_with_subcommands = true, version, about, long_about = None)]
 pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     /// paths in which to recursively search for dead files
     pub paths: Vec<PathBuf>,
 
-    /// paths to ignore when searching for dead files
-    #[clap(short = 'I', long)]
-    pub ignore_paths: Vec<PathBuf>,
+    /// file listing additional paths (one per line) in which to search for dead files
+    #[clap(long, value_name = "FILE")]
+    pub paths_from: Option<PathBuf>,
+
+    /// also read paths (one per line) from stdin, e.g. `git diff --name-only | undead --stdin`
+    #[clap(long)]
+    pub stdin: bool,
+
+    /// glob patterns of paths to ignore when searching for dead files (e.g. `tests`, `*.pyi` or
+    /// `**/migrations/**`), matching at any depth unless absolute, comma-separated in UNDEAD_IGNORE
+    #[clap(
+        short = 'I',
+        long,
+        alias = "ignore-paths",
+        value_name = "GLOB",
+        env = "UNDEAD_IGNORE"
+    )]
+    pub ignore_globs: Vec<String>,
+
+    /// regexes matched against full paths to ignore when searching for dead files
+    #[clap(long, value_name = "PATTERN")]
+    pub ignore_regex: Vec<String>,
+
+    /// dotted modules to always consider as used (e.g. loaded dynamically)
+    #[clap(long, value_name = "MODULE")]
+    pub assume_used: Vec<String>,
+
+    /// directory to resolve absolute `from` imports against instead of the project root
+    #[clap(long, value_name = "DIR")]
+    pub relative_import_base: Option<PathBuf>,
+
+    /// directory on the import path alongside the project root, all of which form a single
+    /// import namespace (e.g. `src`, whose modules can still be imported as `src.<module>`)
+    #[clap(long = "namespace-root", value_name = "DIR")]
+    pub namespace_roots: Vec<PathBuf>,
+
+    /// directory dotted module names are computed from, when it isn't the project root (e.g. `src`)
+    #[clap(long, value_name = "DIR", env = "UNDEAD_ROOT")]
+    pub modules_root: Option<PathBuf>,
+
+    /// how to find the project root imports are resolved against [default: the closest directory
+    /// with any marker]
+    #[clap(long, value_enum, value_name = "STRATEGY")]
+    pub root_detection: Option<RootDetection>,
+
+    /// don't count imports under `if TYPE_CHECKING:` blocks, which only type checkers run
+    #[clap(long)]
+    pub ignore_type_checking_imports: bool,
+
+    /// don't count imports made by test files (e.g. under `tests/`, or named `test_*.py`), so that
+    /// modules only tests import are reported as dead
+    #[clap(long)]
+    pub exclude_tests_from_imports: bool,
+
+    /// how to parse python files
+    #[clap(long, value_enum, default_value_t = ParseMode::Module)]
+    pub parse_mode: ParseMode,
+
+    /// also report files imported by at most N other files
+    #[clap(long, value_name = "N")]
+    pub near_dead: Option<usize>,
+
+    /// also report package `__init__.py` files nothing imports
+    #[clap(long)]
+    pub include_init: bool,
+
+    /// never report empty package `__init__.py` files, even with --include-init
+    #[clap(long, conflicts_with = "report_empty_init")]
+    pub ignore_empty_init: bool,
+
+    /// also report empty package `__init__.py` files nothing imports, even without --include-init
+    #[clap(long)]
+    pub report_empty_init: bool,
+
+    /// only report dead files last committed at least DAYS ago (files unknown to git are always
+    /// reported)
+    #[clap(long, value_name = "DAYS")]
+    pub min_age: Option<u64>,
+
+    /// only report dead files with at least this confidence
+    #[clap(long, value_enum, value_name = "LEVEL")]
+    pub min_confidence: Option<Confidence>,
+
+    /// directory whose python files are all standalone scripts, and thus entrypoints
+    #[clap(long = "treat-scripts-dir-as-entrypoints", value_name = "DIR")]
+    pub scripts_dirs: Vec<PathBuf>,
+
+    /// don't treat the `scripts` and `bin` directories of project roots as entrypoints
+    #[clap(long)]
+    pub no_default_scripts_dirs: bool,
+
+    /// don't treat files starting with a python shebang (e.g. `#!/usr/bin/env python`) as
+    /// entrypoints
+    #[clap(long)]
+    pub no_shebang_entrypoints: bool,
+
+    /// treat files calling `asyncio.run(...)` at module level as entrypoints, even without a
+    /// `__name__` guard
+    #[clap(long)]
+    pub asyncio_run_entrypoints: bool,
+
+    /// known dead files not to report again, listed one relative path or dotted module per line (as
+    /// written by --write-baseline) or as a JSON report (from `--format json`), can be repeated
+    #[clap(long = "baseline", value_name = "FILE")]
+    pub baselines: Vec<PathBuf>,
+
+    /// write the dead files found to FILE, for later runs to only report new ones with --baseline
+    #[clap(long, value_name = "FILE", conflicts_with = "baselines")]
+    pub write_baseline: Option<PathBuf>,
+
+    /// only report files changed (in git) since REF, for pull request checks, caching imports
+    /// between runs as --cache does
+    #[clap(long, value_name = "REF")]
+    pub pr_mode: Option<String>,
+
+    /// print how many files are dead out of the candidates of each directory, and exit
+    #[clap(long)]
+    pub print_tree_stats: bool,
+
+    /// show how MODULE (a dotted name or a path) was resolved and what imports it, and exit
+    #[clap(long, value_name = "MODULE")]
+    pub explain: Option<String>,
+
+    /// list the files considered entrypoints, along with why, and exit
+    #[clap(long)]
+    pub list_entrypoints: bool,
+
+    /// list the files that could be dead (every file but entrypoints and exclusions), used or
+    /// not, and exit
+    #[clap(long)]
+    pub list_candidates: bool,
+
+    /// list the directories imports are resolved against, in precedence order, and exit (as a JSON
+    /// array with `--format json`)
+    #[clap(long)]
+    pub print_roots: bool,
+
+    /// cache the imports of unchanged files between runs
+    #[clap(long)]
+    pub cache: bool,
+
+    /// where to store the cache, implies --cache
+    /// [default: $XDG_CACHE_HOME/undead, or .undead_cache in the project root]
+    #[clap(long, value_name = "DIR")]
+    pub cache_dir: Option<PathBuf>,
+
+    /// exit with an error when more than this fraction of the scanned files failed to parse
+    #[clap(long, value_name = "RATIO", value_parser = parse_ratio)]
+    pub fail_on_parse_errors_ratio: Option<f64>,
+
+    /// fail on any file that can't be parsed, instead of skipping it, and on any construct loading
+    /// modules in ways that can't be resolved statically (e.g. `import_module(name)`, a module
+    /// `__getattr__` or a dynamic `__all__`), instead of lowering confidence
+    #[clap(long)]
+    pub strict: bool,
+
+    /// how many threads to walk and analyze files with [default: one per core]
+    #[clap(short = 'j', long, value_name = "N", env = "UNDEAD_THREADS")]
+    pub jobs: Option<NonZeroUsize>,
+
+    /// abort when walking the filesystem takes more than SEC seconds (e.g. because of slow mounts)
+    #[clap(long, value_name = "SEC")]
+    pub max_walk_time: Option<u64>,
+
+    /// don't print every error met while walking the filesystem, only how many there were
+    #[clap(long)]
+    pub quiet_errors: bool,
+
+    /// also report groups of files with identical contents, often copy-pasted dead code
+    #[clap(long)]
+    pub report_duplicates: bool,
+
+    /// print dead files as dotted module names instead of paths
+    #[clap(long)]
+    pub output_modules: bool,
+
+    /// how to print the paths of dead files, whose links always point to their absolute path
+    #[clap(long, value_enum, default_value_t = PathStyle::Module, conflicts_with = "output_modules")]
+    pub path_style: PathStyle,
+
+    /// only print how many dead files were found, whatever the format
+    #[clap(long)]
+    pub count: bool,
+
+    /// print separators and stats to stderr, leaving only dead files on stdout
+    #[clap(long)]
+    pub stats_to_stderr: bool,
+
+    /// check that the installation works by analyzing a tiny bundled project
+    #[clap(long, hide = true)]
+    pub verify: bool,
+
+    /// exit with code 1 when dead files are found, for CI checks (0 when none are, and 1 on errors
+    /// as always)
+    #[clap(long)]
+    pub exit_code: bool,
+
+    /// prepend a `# undead: dead` comment to each dead file that doesn't have it yet, after asking
+    /// for confirmation
+    #[clap(long)]
+    pub annotate: bool,
+
+    /// delete the dead files (but package `__init__.py` files), after asking for confirmation
+    #[clap(long, conflicts_with = "annotate")]
+    pub fix: bool,
+
+    /// don't ask for confirmation before modifying files
+    #[clap(short = 'y', long)]
+    pub yes: bool,
+
+    /// also write the stats as a JSON object to stderr, whatever the format
+    #[clap(long)]
+    pub summary_json: bool,
+
+    /// also write the report to DIR, once per format given with --formats
+    #[clap(long, value_name = "DIR")]
+    pub out_dir: Option<PathBuf>,
+
+    /// comma-separated formats written to --out-dir, among json, html, markdown, csv and sarif
+    #[clap(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        requires = "out_dir",
+        default_values_t = [Format::Json, Format::Csv]
+    )]
+    pub formats: Vec<Format>,
+
+    /// order of the dead files
+    #[clap(long, value_enum, default_value_t = SortOrder::Path)]
+    pub sort: SortOrder,
+
+    /// how to display dead files
+    #[clap(long, value_enum, default_value_t = Format::Pretty, env = "UNDEAD_FORMAT")]
+    pub format: Format,
+
+    /// character set used for decorations such as tree branches
+    #[clap(long, value_enum, default_value_t = OutputEncoding::Utf8)]
+    pub output_encoding: OutputEncoding,
+}
+
+impl Cli {
+    /// Parses the command line, or exits, splitting `UNDEAD_IGNORE` into its comma-separated globs.
+    ///
+    /// Globs given on the command line are left whole, as they may contain commas themselves
+    /// (e.g. `{a,b}.py`).
+    pub fn parse_command_line() -> Cli {
+        let matches = Cli::command().get_matches();
+        let mut cli = Cli::from_arg_matches(&matches).unwrap_or_else(|error| error.exit());
+        if matches.value_source("ignore_globs") == Some(ValueSource::EnvVariable) {
+            cli.ignore_globs = cli
+                .ignore_globs
+                .iter()
+                .flat_map(|globs| globs.split(','))
+                .filter(|glob| !glob.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
+        cli
+    }
+}
+
+fn parse_ratio(value: &str) -> Result<f64, String> {
+    let ratio: f64 = value
+        .parse()
+        .map_err(|_| format!("`{value}` isn't a number"))?;
+    if (0.0..=1.0).contains(&ratio) {
+        Ok(ratio)
+    } else {
+        Err(format!("`{value}` isn't between 0.0 and 1.0"))
+    }
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// compare two JSON reports (from `--format json`) and show what changed
+    Diff {
+        /// the earlier report
+        old: PathBuf,
+        /// the later report
+        new: PathBuf,
+    },
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RootDetection {
+    /// the closest directory with a `setup.py` or `pyproject.toml`
+    Markers,
+    /// the closest git repository
+    Git,
+    /// the directory of the first target path
+    None,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// one dead file per line
+    Pretty,
+    /// dead files laid out as a directory tree
+    HumanTree,
+    /// the whole report as a JSON object, along with its stats
+    Json,
+    /// a self-contained HTML page with a sortable table of dead files
+    Html,
+    /// a Markdown table of dead files, e.g. for pull request comments
+    Markdown,
+    /// one `path,module,confidence` row per dead file
+    Csv,
+    /// a SARIF 2.1.0 log with one result per dead file, e.g. for GitHub code scanning
+    Sarif,
+}
+
+impl Format {
+    /// Extension of the files holding reports in this format, for those writing a whole document.
+    pub fn file_extension(&self) -> Option<&'static str> {
+        match self {
+            Format::Pretty | Format::HumanTree => None,
+            Format::Json => Some("json"),
+            Format::Html => Some("html"),
+            Format::Markdown => Some("md"),
+            Format::Csv => Some("csv"),
+            Format::Sarif => Some("sarif"),
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PathStyle {
+    /// relative to the import root, as the file is imported
+    Module,
+    /// relative to the current directory
+    Relative,
+    /// absolute
+    Absolute,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortOrder {
+    /// by path
+    Path,
+    /// by line count, the largest files (and cleanups) first
+    Lines,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputEncoding {
+    #[default]
+    Utf8,
+    /// only use ASCII characters, for legacy consoles
+    Ascii,
 }
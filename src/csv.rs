@@ -0,0 +1,49 @@
+//! Rendering of reports as CSV, for spreadsheets and scripts.
+use std::io::Write;
+
+use clap::ValueEnum;
+
+use crate::Report;
+
+/// Writes one `path,module,confidence` row per dead file of `report`, after a header row.
+pub fn write_report(report: &Report, stream: &mut dyn Write) -> std::io::Result<()> {
+    writeln!(stream, "path,module,confidence")?;
+    for dead_file in report.dead_files.iter() {
+        let confidence = dead_file.confidence.to_possible_value();
+        writeln!(
+            stream,
+            "{},{},{}",
+            escape(&dead_file.repr),
+            escape(&dead_file.module()),
+            confidence.as_ref().map_or("", |value| value.get_name())
+        )?;
+    }
+    Ok(())
+}
+
+/// Quotes fields that would otherwise be split or garbled, as RFC 4180 describes.
+fn escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn test_fields_are_quoted_when_needed() {
+        let report = Report::for_test(Path::new("/project"), &["a.py", "b,c.py"], 2);
+        let mut output = vec![];
+        write_report(&report, &mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "path,module,confidence\na.py,a,high\n\"b,c.py\",\"b,c\",high\n"
+        );
+        assert_eq!(escape("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+}
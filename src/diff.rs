@@ -0,0 +1,86 @@
+//! Comparison of two JSON reports, to follow how dead code evolves over time.
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::{count_lines, DeadFile, Report};
+
+/// What changed between two reports.
+#[derive(Debug, PartialEq)]
+pub struct ReportDiff {
+    /// files dead in the new report only
+    pub newly_dead: Vec<DeadFile>,
+    /// files dead in the old report only
+    pub resolved: Vec<DeadFile>,
+    /// change in the number of dead files
+    pub count_change: isize,
+    /// change in the number of dead lines, counting files that no longer exist as empty
+    pub lines_change: isize,
+}
+
+/// Reads the JSON report (as written by `--format json`) at `path`.
+pub fn read_report(path: &Path) -> anyhow::Result<Report> {
+    let contents = std::fs::read_to_string(path)?;
+    serde_json::from_str(&contents)
+        .map_err(|error| anyhow::anyhow!("invalid report {}: {}", path.display(), error))
+}
+
+/// Compares the dead files of `old` and `new`, matching them by full path.
+pub fn diff_reports(old: &Report, new: &Report) -> ReportDiff {
+    let old_paths: HashSet<&PathBuf> = old.dead_files.iter().map(|f| &f.full_path).collect();
+    let new_paths: HashSet<&PathBuf> = new.dead_files.iter().map(|f| &f.full_path).collect();
+    let mut newly_dead: Vec<DeadFile> = new
+        .dead_files
+        .iter()
+        .filter(|dead_file| !old_paths.contains(&dead_file.full_path))
+        .cloned()
+        .collect();
+    let mut resolved: Vec<DeadFile> = old
+        .dead_files
+        .iter()
+        .filter(|dead_file| !new_paths.contains(&dead_file.full_path))
+        .cloned()
+        .collect();
+    newly_dead.sort();
+    resolved.sort();
+    let lines = |files: &[DeadFile]| -> isize {
+        files
+            .iter()
+            .map(|file| count_lines(&file.full_path).unwrap_or(0) as isize)
+            .sum()
+    };
+    ReportDiff {
+        count_change: new.dead_files.len() as isize - old.dead_files.len() as isize,
+        lines_change: lines(&newly_dead) - lines(&resolved),
+        newly_dead,
+        resolved,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(dir: &Path, reprs: &[&str]) -> Report {
+        Report::for_test(dir, reprs, 10)
+    }
+
+    #[test]
+    fn test_diff_reports() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("new.py"), "a = 1\nb = 2\nc = 3\n").unwrap();
+        std::fs::write(dir.path().join("kept.py"), "a = 1\n").unwrap();
+        // `gone.py` was deleted since the old report
+        let old = report(dir.path(), &["gone.py", "kept.py"]);
+        let new = report(dir.path(), &["kept.py", "new.py"]);
+
+        let old_path = dir.path().join("old.json");
+        std::fs::write(&old_path, serde_json::to_string(&old).unwrap()).unwrap();
+        let old = read_report(&old_path).unwrap();
+
+        let diff = diff_reports(&old, &new);
+        assert_eq!(diff.newly_dead, report(dir.path(), &["new.py"]).dead_files);
+        assert_eq!(diff.resolved, report(dir.path(), &["gone.py"]).dead_files);
+        assert_eq!(diff.count_change, 0);
+        assert_eq!(diff.lines_change, 3);
+    }
+}
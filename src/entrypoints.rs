@@ -0,0 +1,113 @@
+//! Detection of python files that are run directly rather than imported.
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use grep::{matcher::Matcher, regex::RegexMatcher};
+use serde::{Deserialize, Serialize};
+
+/// Why a file is considered an entrypoint, and thus never dead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum EntrypointReason {
+    /// the file contains an `if __name__ == "__main__":` guard
+    NameGuard,
+    /// the file lives in a directory of standalone scripts
+    ScriptsDir,
+    /// the file starts with a python shebang (e.g. `#!/usr/bin/env python`)
+    Shebang,
+    /// the file is the `__main__.py` of a package run with `python -m`
+    MainModule,
+    /// the file calls `asyncio.run(...)` at module level
+    AsyncioRun,
+}
+
+impl fmt::Display for EntrypointReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EntrypointReason::NameGuard => write!(f, "name-guard"),
+            EntrypointReason::ScriptsDir => write!(f, "scripts-dir"),
+            EntrypointReason::Shebang => write!(f, "shebang"),
+            EntrypointReason::MainModule => write!(f, "main-module"),
+            EntrypointReason::AsyncioRun => write!(f, "asyncio-run"),
+        }
+    }
+}
+
+/// Directories of standalone scripts looked for at the top of every import root, unless
+/// `--no-default-scripts-dirs` is given.
+pub(crate) const DEFAULT_SCRIPTS_DIRS: &[&str] = &["scripts", "bin"];
+
+/// Settings affecting which files are considered entrypoints.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct EntrypointOptions {
+    /// directories whose python files are all entrypoints
+    pub(crate) scripts_dirs: Vec<PathBuf>,
+    /// whether files starting with a python shebang are entrypoints
+    pub(crate) shebangs: bool,
+    /// whether files calling `asyncio.run(...)` at module level are entrypoints
+    pub(crate) asyncio_run: bool,
+}
+
+/// What the source of a file tells about it being run directly, gathered while it is read for
+/// its imports so that it is read only once.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EntrypointMarkers {
+    /// whether it contains an `if __name__ == "__main__":` guard
+    pub name_guard: bool,
+    /// whether it starts with a python shebang
+    pub python_shebang: bool,
+    /// whether it calls `asyncio.run(...)` at module level
+    pub asyncio_run: bool,
+}
+
+impl EntrypointMarkers {
+    /// Looks for entrypoint markers in the source of a python file.
+    pub(crate) fn scan(contents: &str) -> EntrypointMarkers {
+        let name_guard = RegexMatcher::new(r#"if\s+__name__\s*==\s*["']__main__["']:"#).unwrap();
+        // unindented calls run at module level
+        let asyncio_run = RegexMatcher::new(r"^asyncio\.run\(").unwrap();
+        let any_line_matches = |matcher: &RegexMatcher| {
+            contents
+                .lines()
+                .any(|line| matcher.is_match(line.as_bytes()).unwrap_or(false))
+        };
+        EntrypointMarkers {
+            name_guard: any_line_matches(&name_guard),
+            python_shebang: contents
+                .lines()
+                .next()
+                .and_then(|first_line| first_line.strip_prefix("#!"))
+                .is_some_and(|interpreter| interpreter.contains("python")),
+            asyncio_run: any_line_matches(&asyncio_run),
+        }
+    }
+
+    /// Reads the file at `path` to look for entrypoint markers, for when it isn't analyzed.
+    pub(crate) fn read(path: &Path) -> anyhow::Result<EntrypointMarkers> {
+        let contents = std::fs::read(path)?;
+        Ok(EntrypointMarkers::scan(&String::from_utf8_lossy(&contents)))
+    }
+}
+
+/// Returns why the file at `path`, whose source holds `markers`, is an entrypoint, if it is one.
+pub(crate) fn entrypoint_reason(
+    path: &Path,
+    markers: &EntrypointMarkers,
+    options: &EntrypointOptions,
+) -> Option<EntrypointReason> {
+    if path.file_name().is_some_and(|name| name == "__main__.py") {
+        return Some(EntrypointReason::MainModule);
+    }
+    if options.scripts_dirs.iter().any(|dir| path.starts_with(dir)) {
+        return Some(EntrypointReason::ScriptsDir);
+    }
+    if options.shebangs && markers.python_shebang {
+        return Some(EntrypointReason::Shebang);
+    }
+    if markers.name_guard {
+        return Some(EntrypointReason::NameGuard);
+    }
+    if options.asyncio_run && markers.asyncio_run {
+        return Some(EntrypointReason::AsyncioRun);
+    }
+    None
+}
@@ -0,0 +1,75 @@
+//! Errors returned by the library, for callers to tell them apart.
+use std::fmt;
+use std::path::PathBuf;
+
+/// Why a dead code search (or any other library operation) failed.
+#[derive(Debug)]
+pub enum UndeadError {
+    /// no project root could be found for the target paths
+    RootNotFound,
+    /// the target paths contain no python file
+    NoPythonFiles,
+    /// a python file couldn't be parsed
+    ParseFailure { path: PathBuf, message: String },
+    /// reading or writing a file failed
+    Io(std::io::Error),
+    /// the options or inputs (e.g. a regex or a baseline) are invalid
+    Config(String),
+}
+
+impl fmt::Display for UndeadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UndeadError::RootNotFound => write!(f, "couldn't find the root of the python project"),
+            UndeadError::NoPythonFiles => write!(f, "no Python files found under the given paths"),
+            // the path is left out, since failures are usually listed along with their file
+            UndeadError::ParseFailure { message, .. } => write!(f, "{message}"),
+            UndeadError::Io(error) => write!(f, "{error}"),
+            UndeadError::Config(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl std::error::Error for UndeadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            UndeadError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for UndeadError {
+    fn from(error: std::io::Error) -> Self {
+        UndeadError::Io(error)
+    }
+}
+
+/// Internals report errors with `anyhow`, which get sorted out at the library boundary.
+impl From<anyhow::Error> for UndeadError {
+    fn from(error: anyhow::Error) -> Self {
+        let error = match error.downcast::<UndeadError>() {
+            Ok(error) => return error,
+            Err(error) => error,
+        };
+        match error.downcast::<std::io::Error>() {
+            Ok(error) => UndeadError::Io(error),
+            Err(error) => UndeadError::Config(format!("{error:#}")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_anyhow() {
+        let error = UndeadError::from(anyhow::Error::new(UndeadError::NoPythonFiles));
+        assert!(matches!(error, UndeadError::NoPythonFiles));
+        let error = UndeadError::from(anyhow::Error::new(std::io::Error::other("disk full")));
+        assert!(matches!(error, UndeadError::Io(_)));
+        let error = UndeadError::from(anyhow::anyhow!("invalid regex"));
+        assert!(matches!(error, UndeadError::Config(message) if message == "invalid regex"));
+    }
+}
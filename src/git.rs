@@ -0,0 +1,86 @@
+//! Queries about the git history of project files.
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// When the files of the repositories met were last committed, read with one `git log` per
+/// repository rather than one per file.
+#[derive(Default)]
+pub(crate) struct CommitTimes {
+    repositories: RefCell<HashMap<PathBuf, HashMap<PathBuf, SystemTime>>>,
+}
+
+impl CommitTimes {
+    /// Time elapsed since the most recent commit touching `path` (a canonical path), or `None` if
+    /// the file isn't tracked by git (or git isn't available).
+    pub(crate) fn last_commit_age(&self, path: &Path) -> Option<Duration> {
+        let top_level = path
+            .ancestors()
+            .skip(1)
+            .find(|dir| dir.join(".git").exists())?;
+        let mut repositories = self.repositories.borrow_mut();
+        let commit_times = repositories
+            .entry(top_level.to_path_buf())
+            .or_insert_with(|| last_commit_times(top_level).unwrap_or_default());
+        let committed_at = *commit_times.get(path)?;
+        Some(
+            SystemTime::now()
+                .duration_since(committed_at)
+                .unwrap_or_default(),
+        )
+    }
+}
+
+/// Reads the time of the most recent commit touching each file of the repository at `top_level`.
+fn last_commit_times(top_level: &Path) -> anyhow::Result<HashMap<PathBuf, SystemTime>> {
+    // commits come newest first, each as a NUL-prefixed timestamp followed by the files it touched
+    let log = run_git(
+        top_level,
+        &[
+            "-c",
+            "core.quotePath=false",
+            "log",
+            "--format=%x00%ct",
+            "--name-only",
+        ],
+    )?;
+    let mut commit_times = HashMap::new();
+    let mut committed_at = UNIX_EPOCH;
+    for line in log.lines() {
+        if let Some(timestamp) = line.strip_prefix('\0') {
+            committed_at = UNIX_EPOCH + Duration::from_secs(timestamp.trim().parse()?);
+        } else if !line.is_empty() {
+            commit_times
+                .entry(top_level.join(line))
+                .or_insert(committed_at);
+        }
+    }
+    Ok(commit_times)
+}
+
+/// Lists the files of the repository containing `dir` that differ from `reference`, including
+/// uncommitted and untracked ones, as canonical paths.
+pub(crate) fn changed_files(dir: &Path, reference: &str) -> anyhow::Result<HashSet<PathBuf>> {
+    let top_level = PathBuf::from(run_git(dir, &["rev-parse", "--show-toplevel"])?.trim());
+    let changed = run_git(&top_level, &["diff", "--name-only", reference, "--"])?;
+    let untracked = run_git(&top_level, &["ls-files", "--others", "--exclude-standard"])?;
+    Ok(changed
+        .lines()
+        .chain(untracked.lines())
+        .filter_map(|path| top_level.join(path).canonicalize().ok())
+        .collect())
+}
+
+fn run_git(dir: &Path, args: &[&str]) -> anyhow::Result<String> {
+    let output = Command::new("git").arg("-C").arg(dir).args(args).output()?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "`git {}` failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8(output.stdout)?)
+}
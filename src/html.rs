@@ -0,0 +1,103 @@
+//! Rendering of reports as a self-contained HTML page, to share results outside of a terminal.
+use std::io::Write;
+use std::time::Duration;
+
+use crate::printer::file_uri;
+use crate::{count_lines, Report};
+
+const STYLE: &str = "
+body { font-family: sans-serif; margin: 2em; color: #222; }
+table { border-collapse: collapse; }
+th, td { padding: 0.3em 1em; text-align: left; border-bottom: 1px solid #ddd; }
+th { cursor: pointer; user-select: none; background: #f4f4f4; }
+td.lines { text-align: right; }
+";
+
+// sorts the table by the clicked column, toggling the order on repeated clicks
+const SCRIPT: &str = "
+document.querySelectorAll('th').forEach((header, column) => {
+  header.addEventListener('click', () => {
+    const body = header.closest('table').tBodies[0];
+    const ascending = header.dataset.order !== 'asc';
+    header.dataset.order = ascending ? 'asc' : 'desc';
+    const key = (row) => row.cells[column].dataset.sort ?? row.cells[column].textContent;
+    const rows = Array.from(body.rows).sort((a, b) =>
+      key(a).localeCompare(key(b), undefined, { numeric: true }) * (ascending ? 1 : -1));
+    rows.forEach((row) => body.appendChild(row));
+  });
+});
+";
+
+/// Writes `report` as an HTML page holding a sortable table of the dead files and the stats.
+pub fn write_report(
+    report: &Report,
+    duration: Duration,
+    stream: &mut dyn Write,
+) -> std::io::Result<()> {
+    writeln!(stream, "<!DOCTYPE html>")?;
+    writeln!(stream, "<html lang=\"en\">")?;
+    writeln!(stream, "<head>")?;
+    writeln!(stream, "<meta charset=\"utf-8\">")?;
+    writeln!(stream, "<title>undead report</title>")?;
+    writeln!(stream, "<style>{STYLE}</style>")?;
+    writeln!(stream, "</head>")?;
+    writeln!(stream, "<body>")?;
+    writeln!(stream, "<h1>Dead files</h1>")?;
+    writeln!(
+        stream,
+        "<p>{} dead files out of {} scanned in {:?}.</p>",
+        report.dead_files.len(),
+        report.scanned_files,
+        duration
+    )?;
+    writeln!(stream, "<table>")?;
+    writeln!(
+        stream,
+        "<thead><tr><th>Module</th><th>Path</th><th>Lines</th></tr></thead>"
+    )?;
+    writeln!(stream, "<tbody>")?;
+    for dead_file in report.dead_files.iter() {
+        let lines = count_lines(&dead_file.full_path);
+        writeln!(
+            stream,
+            "<tr><td>{}</td><td><a href=\"{}\">{}</a></td><td class=\"lines\" data-sort=\"{}\">{}</td></tr>",
+            escape(&dead_file.module()),
+            escape(&file_uri(&dead_file.full_path.to_string_lossy())),
+            escape(&dead_file.repr),
+            lines.unwrap_or(0),
+            lines.map_or_else(|| "?".to_string(), |lines| lines.to_string()),
+        )?;
+    }
+    writeln!(stream, "</tbody>")?;
+    writeln!(stream, "</table>")?;
+    writeln!(stream, "<script>{SCRIPT}</script>")?;
+    writeln!(stream, "</body>")?;
+    writeln!(stream, "</html>")
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rows_escape_paths_and_sort_by_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.py"), "import os\nimport sys\n").unwrap();
+        let report = Report::for_test(dir.path(), &["a.py", "c<d>.py"], 5);
+
+        let mut html = vec![];
+        write_report(&report, Duration::ZERO, &mut html).unwrap();
+        let html = String::from_utf8(html).unwrap();
+
+        assert!(html.contains("<td class=\"lines\" data-sort=\"2\">2</td>"));
+        assert!(html.contains("c&lt;d&gt;.py"));
+        assert!(!html.contains("c<d>.py"));
+    }
+}
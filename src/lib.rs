@@ -0,0 +1,4350 @@
+//! Search for dead (never imported) files in Python projects.
+use crate::cli::{Cli, RootDetection, SortOrder};
+use cache::ImportCache;
+use crossbeam::queue::SegQueue;
+use entrypoints::{EntrypointMarkers, EntrypointOptions, EntrypointReason};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use grep::{matcher::Matcher, regex::RegexMatcher};
+use ignore::{types::TypesBuilder, DirEntry, WalkBuilder};
+use setup_py::SetupDeclarations;
+
+use clap::ValueEnum;
+use rayon::prelude::*;
+use rustpython_ast::{
+    Constant, Expr, ExprCall, Mod, ModInteractive, ModModule, Stmt, StmtAnnAssign, StmtAssign,
+    StmtAugAssign, StmtIf, StmtImport, StmtImportFrom, Visitor,
+};
+use rustpython_parser::{parse, Mode};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Mutex};
+use std::time::{Duration, Instant};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    path::{Component, Path, PathBuf, MAIN_SEPARATOR_STR},
+};
+
+pub mod annotate;
+pub mod baseline;
+mod cache;
+pub mod cli;
+pub mod csv;
+pub mod diff;
+pub mod entrypoints;
+mod error;
+mod git;
+pub mod html;
+pub mod markdown;
+pub mod printer;
+mod pyproject;
+pub mod sarif;
+mod setup_py;
+mod stdlib;
+pub mod verify;
+
+pub use error::UndeadError;
+
+/// The outcome of a dead code search.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Report {
+    pub dead_files: Vec<DeadFile>,
+    /// files imported by few enough other files to be likely to become dead soon
+    pub near_dead_files: Vec<NearDeadFile>,
+    pub scanned_files: usize,
+    pub warnings: Vec<String>,
+    /// files left out of the search because they couldn't be read or parsed
+    pub skipped: Vec<SkippedFile>,
+    /// groups of files with identical contents, when asked for
+    #[serde(default)]
+    pub duplicates: Vec<Vec<String>>,
+}
+
+impl Report {
+    /// Assembles the report of a streamed search, sorting its dead files.
+    pub fn new(mut dead_files: Vec<DeadFile>, summary: Summary) -> Report {
+        dead_files.sort();
+        Report {
+            dead_files,
+            near_dead_files: summary.near_dead_files,
+            scanned_files: summary.scanned_files,
+            warnings: summary.warnings,
+            skipped: summary.skipped,
+            duplicates: summary.duplicates,
+        }
+    }
+
+    /// Sorts the dead files in `order`, ties being sorted by path.
+    pub fn sort(&mut self, order: SortOrder) {
+        match order {
+            SortOrder::Path => self.dead_files.sort(),
+            SortOrder::Lines => self.dead_files.sort_by_cached_key(|dead_file| {
+                (
+                    std::cmp::Reverse(count_lines(&dead_file.full_path).unwrap_or(0)),
+                    dead_file.clone(),
+                )
+            }),
+        }
+    }
+
+    /// Fraction of the scanned files that couldn't be read or parsed.
+    pub fn parse_errors_ratio(&self) -> f64 {
+        if self.scanned_files == 0 {
+            return 0.0;
+        }
+        self.skipped.len() as f64 / self.scanned_files as f64
+    }
+}
+
+#[cfg(test)]
+impl Report {
+    /// A report of `scanned_files` files for tests, the dead ones being `reprs` under `root`.
+    pub(crate) fn for_test(root: &Path, reprs: &[&str], scanned_files: usize) -> Report {
+        let dead_files = reprs
+            .iter()
+            .map(|repr| DeadFile::for_test(repr, root.join(repr)))
+            .collect();
+        Report::new(
+            dead_files,
+            Summary {
+                scanned_files,
+                ..Default::default()
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+impl DeadFile {
+    /// A file found dead with high confidence, for tests.
+    pub(crate) fn for_test(repr: &str, full_path: impl Into<PathBuf>) -> DeadFile {
+        DeadFile {
+            repr: repr.to_string(),
+            full_path: full_path.into(),
+            confidence: Confidence::High,
+            relative_path: Default::default(),
+        }
+    }
+}
+
+/// Everything a dead code search found besides the dead files themselves.
+#[derive(Debug, Default)]
+pub struct Summary {
+    pub near_dead_files: Vec<NearDeadFile>,
+    pub scanned_files: usize,
+    /// diagnostics about the project that may affect the results
+    pub warnings: Vec<String>,
+    pub skipped: Vec<SkippedFile>,
+    /// groups of files with identical contents, by path relative to their import root
+    pub duplicates: Vec<Vec<String>>,
+}
+
+/// A python file that is neither an entrypoint nor imported anywhere.
+///
+/// Besides its fields, it serializes as its dotted `module` name, for the convenience of other
+/// tools.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct DeadFile {
+    /// path of the file relative to its import root
+    pub repr: String,
+    /// absolute path of the file
+    pub full_path: PathBuf,
+    /// how sure we are that nothing loads the file
+    pub confidence: Confidence,
+    /// path of the file relative to the directory the search ran from (or absolute, when that
+    /// isn't known)
+    #[serde(default)]
+    pub relative_path: PathBuf,
+}
+
+/// How likely a file reported dead is to really be unused.
+#[derive(
+    ValueEnum, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Confidence {
+    /// the project imports modules dynamically by name (`importlib`, `__import__`)
+    Low,
+    /// the project looks attributes up on modules dynamically (`getattr(module, ...)`)
+    Medium,
+    /// the project only uses import statements, and doesn't ship the file in a package
+    High,
+}
+
+impl From<Option<DynamicImport>> for Confidence {
+    fn from(dynamic_import: Option<DynamicImport>) -> Self {
+        match dynamic_import {
+            None => Confidence::High,
+            Some(DynamicImport::Getattr) => Confidence::Medium,
+            Some(DynamicImport::Importlib) => Confidence::Low,
+        }
+    }
+}
+
+/// Dead files sort by path whatever the casing and separators, then by exact path and full path for
+/// the order never to depend on the platform or on the order they were found in.
+impl Ord for DeadFile {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        let sort_key = |file: &DeadFile| file.repr.to_lowercase().replace('\\', "/");
+        sort_key(self)
+            .cmp(&sort_key(other))
+            .then_with(|| self.repr.cmp(&other.repr))
+            .then_with(|| self.full_path.cmp(&other.full_path))
+            .then_with(|| self.confidence.cmp(&other.confidence))
+    }
+}
+
+impl PartialOrd for DeadFile {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Serialize for DeadFile {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut state = serializer.serialize_struct("DeadFile", 5)?;
+        state.serialize_field("repr", &self.repr)?;
+        state.serialize_field("full_path", &self.full_path)?;
+        state.serialize_field("confidence", &self.confidence)?;
+        state.serialize_field("relative_path", &self.relative_path)?;
+        state.serialize_field("module", &self.module())?;
+        state.end()
+    }
+}
+
+/// The path leading from the `base` directory to `path`, both absolute.
+pub fn relative_path(path: &Path, base: &Path) -> PathBuf {
+    let path_components: Vec<_> = path.components().collect();
+    let base_components: Vec<_> = base.components().collect();
+    let common = path_components
+        .iter()
+        .zip(base_components.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+    // paths on different drives share no root to start from
+    if common == 0 {
+        return path.to_path_buf();
+    }
+    std::iter::repeat_n(Component::ParentDir, base_components.len() - common)
+        .chain(path_components[common..].iter().copied())
+        .collect()
+}
+
+impl DeadFile {
+    /// The dotted name the file is imported with, e.g. `app.utils.helpers`.
+    pub fn module(&self) -> String {
+        let module = self
+            .repr
+            .strip_suffix(PYTHON_EXTENSION)
+            .unwrap_or(&self.repr)
+            .replace(MAIN_SEPARATOR_STR, ".");
+        match module.strip_suffix(&format!(".{PYTHON_INIT_MODULE}")) {
+            Some(package) => package.to_string(),
+            None => module,
+        }
+    }
+}
+
+/// A python file imported by at most `--near-dead` other files.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct NearDeadFile {
+    pub file: DeadFile,
+    pub importers: usize,
+}
+
+/// A python file whose imports are unknown because it couldn't be read or parsed.
+///
+/// Such files are never reported as dead, since they may well be entrypoints.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct SkippedFile {
+    /// path of the file relative to its import root
+    pub repr: String,
+    pub full_path: PathBuf,
+    pub reason: String,
+}
+
+/// A python file that is run directly rather than imported.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Entrypoint {
+    /// path of the file relative to its import root
+    pub repr: String,
+    pub full_path: PathBuf,
+    pub reason: EntrypointReason,
+}
+
+/// Lists the entrypoints found in the target paths, sorted by path.
+pub fn find_entrypoints(cli: &Cli) -> Result<Vec<Entrypoint>, UndeadError> {
+    let walk_monitor = WalkMonitor::new(cli);
+    let WalkedProject {
+        target_files: walked_paths,
+        import_roots,
+        ..
+    } = walk_project(cli, &walk_monitor)?;
+    if let Some(summary) = walk_monitor.summary() {
+        eprintln!("{summary}");
+    }
+    let entrypoint_options = entrypoint_options(cli, &import_roots)?;
+    let mut found_entrypoints = walked_paths
+        .into_par_iter()
+        .map(|path| {
+            let markers = EntrypointMarkers::read(&path)?;
+            match entrypoints::entrypoint_reason(&path, &markers, &entrypoint_options) {
+                Some(reason) => {
+                    let import_root = import_root_for(&path, &import_roots);
+                    Ok(Some(Entrypoint {
+                        repr: path
+                            .strip_prefix(import_root)
+                            .unwrap_or(&path)
+                            .to_string_lossy()
+                            .to_string(),
+                        full_path: path,
+                        reason,
+                    }))
+                }
+                None => Ok(None),
+            }
+        })
+        .filter_map(|entrypoint| entrypoint.transpose())
+        .collect::<anyhow::Result<Vec<Entrypoint>>>()?;
+    found_entrypoints.sort_by(|a, b| a.full_path.cmp(&b.full_path));
+    Ok(found_entrypoints)
+}
+
+/// Lists the files that could be reported as dead, i.e. neither entrypoints nor excluded, whether
+/// or not something imports them, sorted by path.
+pub fn find_candidates(cli: &Cli) -> Result<Vec<DeadFile>, UndeadError> {
+    let mut candidates = Vec::new();
+    scan_candidates(cli, |file, _| candidates.push(file))?;
+    candidates.sort_by(|a, b| a.full_path.cmp(&b.full_path));
+    Ok(candidates)
+}
+
+/// A directory imports are resolved against.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ImportRoot {
+    pub path: PathBuf,
+    pub kind: ImportRootKind,
+}
+
+/// Why a directory is an import root.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ImportRootKind {
+    /// given with `--relative-import-base`, for absolute `from` imports
+    RelativeImportBase,
+    /// given with `--modules-root`, standing for the project root
+    ModulesRoot,
+    /// the detected root of the project
+    ProjectRoot,
+    /// the detected root of a target path outside of the project root
+    TargetRoot,
+    /// given with `--namespace-root`
+    NamespaceRoot,
+}
+
+impl fmt::Display for ImportRootKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportRootKind::RelativeImportBase => write!(f, "relative-import-base"),
+            ImportRootKind::ModulesRoot => write!(f, "modules-root"),
+            ImportRootKind::ProjectRoot => write!(f, "project-root"),
+            ImportRootKind::TargetRoot => write!(f, "target-root"),
+            ImportRootKind::NamespaceRoot => write!(f, "namespace-root"),
+        }
+    }
+}
+
+/// Lists the directories imports are resolved against, in precedence order: the relative import
+/// base of absolute `from` imports if any, then the project root, the roots of targets outside of
+/// it and the namespace roots.
+pub fn find_import_roots(cli: &Cli) -> Result<Vec<ImportRoot>, UndeadError> {
+    let target_paths = resolve_paths(collect_target_paths(cli)?)?;
+    if target_paths.is_empty() {
+        return Err(UndeadError::NoPythonFiles);
+    }
+    let mut import_roots = Vec::new();
+    if let Some(base) = &cli.relative_import_base {
+        import_roots.push(ImportRoot {
+            path: strip_extended_length_prefix(base.canonicalize()?),
+            kind: ImportRootKind::RelativeImportBase,
+        });
+    }
+    import_roots.extend(detect_import_roots(cli, &target_paths)?.ok_or(UndeadError::RootNotFound)?);
+    Ok(import_roots)
+}
+
+/// How many of the candidate files of a directory are dead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirectoryStats {
+    /// path of the directory relative to its import root, `.` for the root itself
+    pub repr: String,
+    pub dead_files: usize,
+    pub total_files: usize,
+}
+
+/// Counts dead and candidate files per directory, the directories with the most dead files first.
+pub fn find_tree_stats(cli: &Cli) -> Result<Vec<DirectoryStats>, UndeadError> {
+    let is_reported = reported_filter(cli)?;
+    let mut directories: BTreeMap<String, DirectoryStats> = BTreeMap::new();
+    scan_candidates(cli, |file, is_dead| {
+        let repr = match Path::new(&file.repr).parent() {
+            Some(parent) if parent != Path::new("") => parent.to_string_lossy().to_string(),
+            _ => ".".to_string(),
+        };
+        let is_dead = is_reported(&file, is_dead);
+        let stats = directories
+            .entry(repr.clone())
+            .or_insert_with(|| DirectoryStats {
+                repr,
+                dead_files: 0,
+                total_files: 0,
+            });
+        stats.total_files += 1;
+        if is_dead {
+            stats.dead_files += 1;
+        }
+    })?;
+    let mut directories: Vec<DirectoryStats> = directories.into_values().collect();
+    // the map keeps ties sorted by path
+    directories.sort_by_key(|stats| std::cmp::Reverse(stats.dead_files));
+    Ok(directories)
+}
+
+/// How a single module was resolved, to debug unexpected results.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Explanation {
+    /// dotted name the module is looked up with among imports
+    pub module: String,
+    /// the project file the module was found in, if any
+    pub full_path: Option<PathBuf>,
+    /// whether the module is imported, or otherwise considered used
+    pub is_used: bool,
+    /// paths of the files importing the module relative to their import root, sorted
+    pub importers: Vec<String>,
+}
+
+/// Explains how `target`, a dotted module name or a path to a python file, was resolved.
+pub fn explain(cli: &Cli, target: &str) -> Result<Explanation, UndeadError> {
+    let project = ProjectImports::collect(cli)?;
+    let import_roots = &project.import_roots;
+    let target_path = Path::new(target);
+    let (module, full_path) = if target_path.is_file() {
+        let full_path = strip_extended_length_prefix(target_path.canonicalize()?);
+        let module = render_as_import_string(&full_path, import_root_for(&full_path, import_roots));
+        (module, Some(full_path))
+    } else {
+        let relative_path = PathBuf::from(target.replace('.', MAIN_SEPARATOR_STR));
+        import_roots
+            .iter()
+            .find_map(|root| {
+                let module_path = root.join(&relative_path).with_extension("py");
+                let init_path = root.join(&relative_path).join(PYTHON_INIT_FILE);
+                if module_path.is_file() {
+                    Some((target.to_string(), module_path))
+                } else if init_path.is_file() {
+                    Some((format!("{target}.{PYTHON_INIT_MODULE}"), init_path))
+                } else {
+                    None
+                }
+            })
+            .map_or((target.to_string(), None), |(module, path)| {
+                (module, Some(path))
+            })
+    };
+    let mut importers: Vec<String> = project
+        .import_index
+        .get(&module)
+        .into_iter()
+        .flatten()
+        .map(|importer| {
+            importer
+                .strip_prefix(import_root_for(importer, import_roots))
+                .unwrap_or(importer)
+                .to_string_lossy()
+                .to_string()
+        })
+        .collect();
+    importers.sort();
+    Ok(Explanation {
+        is_used: project.used_modules.contains(&module),
+        module,
+        full_path,
+        importers,
+    })
+}
+
+/// Searches for dead files, returning them sorted.
+pub fn find_dead_files(cli: &Cli) -> Result<Report, UndeadError> {
+    let mut dead_files = vec![];
+    let summary = find_dead_files_streaming(cli, |dead_file| dead_files.push(dead_file))?;
+    let mut report = Report::new(dead_files, summary);
+    report.sort(cli.sort);
+    Ok(report)
+}
+
+/// Searches for dead files, handing each one to `callback` as soon as it is found (in no
+/// particular order) instead of collecting them.
+pub fn find_dead_files_streaming(
+    cli: &Cli,
+    mut callback: impl FnMut(DeadFile),
+) -> Result<Summary, UndeadError> {
+    let is_reported = reported_filter(cli)?;
+    let summary = scan_candidates(cli, |file, is_dead| {
+        if is_reported(&file, is_dead) {
+            callback(file)
+        }
+    })?;
+    Ok(summary)
+}
+
+/// Tells whether a candidate file should be reported as dead, given whether it is.
+fn reported_filter(cli: &Cli) -> anyhow::Result<impl Fn(&DeadFile, bool) -> bool + '_> {
+    let min_age = cli
+        .min_age
+        .map(|days| std::time::Duration::from_secs(days * 24 * 60 * 60));
+    // baselines are matched by relative path or module, so that they can be shared between
+    // checkouts
+    let mut baseline = HashSet::new();
+    for path in cli.baselines.iter() {
+        baseline.extend(baseline::read_baseline(path)?);
+    }
+    let commit_times = git::CommitTimes::default();
+    Ok(move |file: &DeadFile, is_dead: bool| {
+        if !is_dead
+            || cli.min_confidence.is_some_and(|min| file.confidence < min)
+            || baseline.contains(&file.repr)
+            || baseline.contains(&file.module())
+        {
+            return false;
+        }
+        // files missing from the git history are always reported
+        match min_age {
+            Some(min_age) => commit_times
+                .last_commit_age(&file.full_path)
+                .is_none_or(|age| age >= min_age),
+            None => true,
+        }
+    })
+}
+
+/// Answers whether files are dead without scanning the project again for every question.
+#[derive(Debug)]
+pub struct DeadnessIndex {
+    /// whether each candidate file is dead, by full path
+    candidates: HashMap<PathBuf, bool>,
+}
+
+impl DeadnessIndex {
+    /// Scans the project once to classify every candidate file.
+    pub fn build(cli: &Cli) -> Result<DeadnessIndex, UndeadError> {
+        let mut candidates = HashMap::new();
+        scan_candidates(cli, |file, is_dead| {
+            candidates.insert(file.full_path, is_dead);
+        })?;
+        Ok(DeadnessIndex { candidates })
+    }
+
+    /// Tells whether the file at `path` is dead, or `None` if it isn't a candidate (e.g. it is an
+    /// entrypoint, a package init or outside of the target paths).
+    pub fn is_dead(&self, path: &Path) -> Option<bool> {
+        let path = strip_extended_length_prefix(path.canonicalize().ok()?);
+        self.candidates.get(&path).copied()
+    }
+}
+
+/// What the files of a project import, gathered before telling which candidates are dead.
+struct ProjectImports {
+    /// candidate files found in the target paths
+    walked_paths: Vec<PathBuf>,
+    import_roots: Vec<PathBuf>,
+    scanned_files: usize,
+    /// files that couldn't be read or parsed, with the reason why
+    failures: Vec<(PathBuf, String)>,
+    /// every imported module, with the files importing it
+    import_index: HashMap<String, HashSet<PathBuf>>,
+    /// modules considered used, imported or not
+    used_modules: HashSet<String>,
+    confidence: Confidence,
+    warnings: Vec<String>,
+    setup_declarations: Vec<SetupDeclarations>,
+    /// what tells whether each analyzed file is an entrypoint, read along with its imports
+    entrypoint_markers: HashMap<PathBuf, EntrypointMarkers>,
+    /// groups of candidate files with identical contents, if asked for
+    duplicates: Vec<Vec<PathBuf>>,
+}
+
+impl ProjectImports {
+    /// Walks the target paths and extracts the imports of every file of their import roots.
+    fn collect(cli: &Cli) -> anyhow::Result<ProjectImports> {
+        let walk_monitor = WalkMonitor::new(cli);
+        let WalkedProject {
+            target_files: walked_paths,
+            all_files: all_paths,
+            import_roots,
+        } = walk_project(cli, &walk_monitor)?;
+        let import_options = ImportOptions {
+            relative_import_base: match &cli.relative_import_base {
+                Some(base) => Some(strip_extended_length_prefix(base.canonicalize()?)),
+                None => None,
+            },
+            namespace_roots: resolve_paths(cli.namespace_roots.clone())?,
+            parse_mode: cli.parse_mode,
+            ignore_type_checking_imports: cli.ignore_type_checking_imports,
+        };
+        let mut cache = match cache_file_path(cli, &import_roots[0]) {
+            Some(cache_path) => {
+                let fingerprint = ImportCache::fingerprint(&all_paths, &import_options);
+                Some((ImportCache::load(&cache_path, fingerprint), cache_path))
+            }
+            None => None,
+        };
+        let compiled_imports = compile_imports(
+            &all_paths,
+            &import_roots,
+            &import_options,
+            cache.as_mut().map(|(cache, _)| cache),
+        )?;
+        // no entry for a removed file survives, since the fingerprint covers the file list
+        if let Some((cache, cache_path)) = cache.as_ref() {
+            cache.save(cache_path)?;
+        }
+        if cli.strict {
+            if let Some((path, reason)) = compiled_imports.failures.iter().min() {
+                return Err(UndeadError::ParseFailure {
+                    path: path.clone(),
+                    message: format!(
+                        "{} files failed to parse with --strict, starting with {}: {}",
+                        compiled_imports.failures.len(),
+                        path.display(),
+                        reason
+                    ),
+                }
+                .into());
+            }
+            if let Some((path, construct)) = compiled_imports.unresolved.iter().min() {
+                anyhow::bail!(
+                    "{} constructs can't be resolved statically with --strict, starting with {} \
+                     in {}, drop --strict to accept a lower confidence instead",
+                    compiled_imports.unresolved.len(),
+                    construct,
+                    path.display()
+                );
+            }
+        }
+        let duplicates = if cli.report_duplicates {
+            find_duplicates(&walked_paths, &compiled_imports.content_hashes)
+        } else {
+            vec![]
+        };
+        let mut imports = compiled_imports.imports;
+        if cli.exclude_tests_from_imports {
+            imports.retain(|(importer, _)| {
+                !is_test_file(importer, import_root_for(importer, &import_roots))
+            });
+        }
+        let mut import_index = index_imports(imports);
+        alias_nested_roots(&mut import_index, &import_roots);
+        // dynamic imports anywhere may hide usages of any file
+        let confidence = Confidence::from(compiled_imports.dynamic_import);
+        let mut warnings = find_stdlib_shadowing(&all_paths, &import_roots);
+        warnings.extend(walk_monitor.summary());
+        let mut dynamic_all_inits = compiled_imports.dynamic_all_inits;
+        dynamic_all_inits.sort();
+        warnings.extend(dynamic_all_inits.iter().map(|path| {
+            format!(
+                "{} builds `__all__` dynamically, considering all of its submodules used",
+                path.display()
+            )
+        }));
+        let mut file_warnings = compiled_imports.warnings;
+        file_warnings.sort();
+        warnings.extend(file_warnings);
+
+        let mut declared_modules: HashSet<String> = cli.assume_used.iter().cloned().collect();
+        // modules shipped by a `setup.py` are public API
+        let setup_declarations: Vec<SetupDeclarations> = import_roots
+            .iter()
+            .map(|root| SetupDeclarations::load(root))
+            .collect();
+        for declarations in setup_declarations.iter() {
+            declared_modules.extend(declarations.py_modules.iter().cloned());
+        }
+        // so are declared scripts, which are run rather than imported
+        for root in import_roots.iter() {
+            declared_modules.extend(collect_entry_point_modules(root).into_iter().map(|module| {
+                resolve_import(submodule_import(
+                    &root.join(module.replace('.', MAIN_SEPARATOR_STR)),
+                    root,
+                ))
+            }));
+        }
+        let mut used_modules: HashSet<String> = import_index.keys().cloned().collect();
+        used_modules.extend(declared_modules.iter().cloned());
+        link_generated_pairs(
+            &all_paths,
+            &import_roots,
+            &import_index,
+            &declared_modules,
+            &mut used_modules,
+        );
+
+        Ok(ProjectImports {
+            walked_paths,
+            import_roots,
+            scanned_files: all_paths.len(),
+            failures: compiled_imports.failures,
+            import_index,
+            used_modules,
+            confidence,
+            warnings,
+            setup_declarations,
+            entrypoint_markers: compiled_imports.entrypoint_markers,
+            duplicates,
+        })
+    }
+}
+
+/// Hands every file that may be reported to `callback` along with whether it is dead.
+fn scan_candidates(cli: &Cli, mut callback: impl FnMut(DeadFile, bool)) -> anyhow::Result<Summary> {
+    let ProjectImports {
+        walked_paths,
+        import_roots,
+        scanned_files,
+        failures,
+        import_index,
+        used_modules,
+        confidence,
+        warnings,
+        setup_declarations,
+        entrypoint_markers,
+        duplicates,
+    } = ProjectImports::collect(cli)?;
+    let failed_paths: HashSet<&PathBuf> = failures.iter().map(|(path, _)| path).collect();
+
+    let entrypoint_options = entrypoint_options(cli, &import_roots)?;
+    // read once, for all dead files to be relative to the same directory
+    let current_dir = std::env::current_dir().ok();
+    // only files changed since the reference can have become dead
+    let changed_files = match &cli.pr_mode {
+        Some(reference) => Some(git::changed_files(&import_roots[0], reference)?),
+        None => None,
+    };
+    let near_dead_queue = SegQueue::<NearDeadFile>::new();
+
+    let (sender, receiver) = mpsc::channel();
+    std::thread::scope(|scope| {
+        scope.spawn(|| {
+            walked_paths
+                .into_par_iter()
+                .filter(|path| {
+                    if let Some(file_name) = path.file_name() {
+                        // package inits aren't reported unless asked, but they still are import
+                        // sources (see `all_paths`)
+                        if file_name == PYTHON_INIT_FILE && !is_init_candidate(cli, path) {
+                            return false;
+                        }
+                    }
+                    changed_files
+                        .as_ref()
+                        .is_none_or(|changed_files| changed_files.contains(path))
+                        && !failed_paths.contains(path)
+                        && entrypoints::entrypoint_reason(
+                            path,
+                            // every candidate was analyzed, unless it failed to
+                            &entrypoint_markers.get(path).copied().unwrap_or_default(),
+                            &entrypoint_options,
+                        )
+                        .is_none()
+                })
+                .for_each_with(sender, |sender, path| {
+                    let import_root = import_root_for(&path, &import_roots);
+                    let module = render_as_import_string(&path, import_root);
+                    let is_packaged = setup_declarations
+                        .iter()
+                        .any(|declarations| declarations.packages_cover(&module, &path));
+                    let dead_file = DeadFile {
+                        repr: module.replace('.', MAIN_SEPARATOR_STR) + PYTHON_EXTENSION,
+                        relative_path: match &current_dir {
+                            Some(current_dir) => relative_path(&path, current_dir),
+                            None => path.clone(),
+                        },
+                        full_path: path,
+                        confidence: if is_packaged {
+                            confidence.min(Confidence::Medium)
+                        } else {
+                            confidence
+                        },
+                    };
+                    let is_dead = !used_modules.contains(&module);
+                    if let (false, Some(max_importers), Some(importers)) =
+                        (is_dead, cli.near_dead, import_index.get(&module))
+                    {
+                        let importers = importers
+                            .iter()
+                            .filter(|importer| **importer != dead_file.full_path)
+                            .count();
+                        if importers <= max_importers && !cli.assume_used.contains(&module) {
+                            near_dead_queue.push(NearDeadFile {
+                                file: dead_file.clone(),
+                                importers,
+                            });
+                        }
+                    }
+                    let _ = sender.send((dead_file, is_dead));
+                });
+        });
+        receiver
+            .into_iter()
+            .for_each(|(file, is_dead)| callback(file, is_dead));
+    });
+
+    let mut near_dead_files: Vec<NearDeadFile> = near_dead_queue.into_iter().collect();
+    near_dead_files.sort();
+    let mut skipped: Vec<SkippedFile> = failures
+        .into_iter()
+        .map(|(path, reason)| SkippedFile {
+            repr: path
+                .strip_prefix(import_root_for(&path, &import_roots))
+                .unwrap_or(&path)
+                .to_string_lossy()
+                .to_string(),
+            full_path: path,
+            reason,
+        })
+        .collect();
+    skipped.sort();
+
+    let duplicates = duplicates
+        .into_iter()
+        .map(|group| {
+            group
+                .iter()
+                .map(|path| {
+                    path.strip_prefix(import_root_for(path, &import_roots))
+                        .unwrap_or(path)
+                        .to_string_lossy()
+                        .to_string()
+                })
+                .collect()
+        })
+        .collect();
+
+    Ok(Summary {
+        near_dead_files,
+        scanned_files,
+        warnings,
+        skipped,
+        duplicates,
+    })
+}
+
+/// Tells whether the package init at `path` may be reported, depending on whether it is empty.
+fn is_init_candidate(cli: &Cli, path: &Path) -> bool {
+    if !cli.ignore_empty_init && !cli.report_empty_init {
+        return cli.include_init;
+    }
+    if is_empty_python_file(path) {
+        cli.report_empty_init
+    } else {
+        cli.include_init
+    }
+}
+
+/// Tells whether the python file at `path` contains nothing but blank lines and comments.
+fn is_empty_python_file(path: &Path) -> bool {
+    std::fs::read_to_string(path).is_ok_and(|contents| {
+        contents
+            .lines()
+            .map(str::trim)
+            .all(|line| line.is_empty() || line.starts_with('#'))
+    })
+}
+
+/// Makes protobuf generated `foo_pb2.py` and `foo_pb2_grpc.py` files live or dead together: the
+/// grpc file always imports its pb2 sibling, which thus only counts as used when something else
+/// uses either of them.
+fn link_generated_pairs(
+    all_paths: &[PathBuf],
+    import_roots: &[PathBuf],
+    import_index: &HashMap<String, HashSet<PathBuf>>,
+    declared_modules: &HashSet<String>,
+    used_modules: &mut HashSet<String>,
+) {
+    for grpc_path in all_paths {
+        let Some(pb2_stem) = grpc_path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(|stem| stem.strip_suffix(GRPC_SUFFIX))
+            .filter(|stem| stem.ends_with(PB2_SUFFIX))
+        else {
+            continue;
+        };
+        let pb2_path = grpc_path.with_file_name(format!("{pb2_stem}{PYTHON_EXTENSION}"));
+        if !pb2_path.is_file() {
+            continue;
+        }
+        let import_root = import_root_for(grpc_path, import_roots);
+        let grpc_module = render_as_import_string(grpc_path, import_root);
+        let pb2_module = render_as_import_string(&pb2_path, import_root);
+        let is_pb2_used = declared_modules.contains(&pb2_module)
+            || import_index
+                .get(&pb2_module)
+                .is_some_and(|importers| importers.iter().any(|importer| importer != grpc_path));
+        if used_modules.contains(&grpc_module) || is_pb2_used {
+            used_modules.insert(grpc_module);
+            used_modules.insert(pb2_module);
+        } else {
+            used_modules.remove(&pb2_module);
+        }
+    }
+}
+
+/// Warns about project modules that shadow a standard library module because they sit at the top
+/// of an import root under the same name.
+fn find_stdlib_shadowing(all_paths: &[PathBuf], import_roots: &[PathBuf]) -> Vec<String> {
+    let mut shadowing_modules = BTreeMap::new();
+    for path in all_paths {
+        let import_root = import_root_for(path, import_roots);
+        let module = render_as_import_string(path, import_root);
+        let top_level_module = module.split('.').next().unwrap_or(&module);
+        if stdlib::is_stdlib_module(top_level_module) {
+            let shadowing_path = if top_level_module == module {
+                path.to_path_buf()
+            } else {
+                import_root.join(top_level_module)
+            };
+            shadowing_modules
+                .entry(top_level_module.to_string())
+                .or_insert(shadowing_path);
+        }
+    }
+    shadowing_modules
+        .into_iter()
+        .map(|(module, path)| {
+            format!(
+                "{} shadows the standard library module `{}`",
+                path.display(),
+                module
+            )
+        })
+        .collect()
+}
+
+/// The python files of a project, found in a single walk.
+struct WalkedProject {
+    /// files in the target paths, which may be reported
+    target_files: Vec<PathBuf>,
+    /// files of every import root (and of targets outside of them), whose imports all count
+    all_files: Vec<PathBuf>,
+    import_roots: Vec<PathBuf>,
+}
+
+/// Finds the python root of the (non-empty) target paths, along with the roots of targets outside
+/// of it and the namespace roots, or `None` when there is no python root.
+fn detect_import_roots(
+    cli: &Cli,
+    target_paths: &[PathBuf],
+) -> anyhow::Result<Option<Vec<ImportRoot>>> {
+    let python_root = match (&cli.modules_root, cli.root_detection) {
+        (Some(modules_root), _) => resolve_paths(vec![modules_root.clone()])?.pop(),
+        // the first target stands for the whole project
+        (None, Some(RootDetection::None)) => {
+            detect_project_root(&target_paths[0], cli.root_detection)
+        }
+        // the first in walk order, for results not to depend on the order of arguments
+        (None, _) => detect_project_root(target_paths.iter().min().unwrap(), cli.root_detection),
+    };
+    let Some(python_root) = python_root else {
+        return Ok(None);
+    };
+    let mut import_roots: Vec<ImportRoot> =
+        collect_import_roots(&python_root, target_paths, cli.root_detection)
+            .into_iter()
+            .map(|path| ImportRoot {
+                kind: if path != python_root {
+                    ImportRootKind::TargetRoot
+                } else if cli.modules_root.is_some() {
+                    ImportRootKind::ModulesRoot
+                } else {
+                    ImportRootKind::ProjectRoot
+                },
+                path,
+            })
+            .collect();
+    for namespace_root in resolve_paths(cli.namespace_roots.clone())? {
+        if !import_roots.iter().any(|root| root.path == namespace_root) {
+            import_roots.push(ImportRoot {
+                path: namespace_root,
+                kind: ImportRootKind::NamespaceRoot,
+            });
+        }
+    }
+    Ok(Some(import_roots))
+}
+
+/// Finds the import roots of the target paths, then walks them once for their python files, the
+/// target ones being picked out of those.
+fn walk_project(cli: &Cli, walk_monitor: &WalkMonitor) -> anyhow::Result<WalkedProject> {
+    let target_paths = resolve_paths(collect_target_paths(cli)?)?;
+    if target_paths.is_empty() {
+        return Err(UndeadError::NoPythonFiles.into());
+    }
+    let walk_filters = WalkFilters {
+        ignore_globs: build_ignore_globs(&cli.ignore_globs)?,
+        ignore_regex: build_ignore_regex(&cli.ignore_regex)?,
+    };
+
+    let Some(import_roots) = detect_import_roots(cli, &target_paths)? else {
+        // walk the targets alone to tell why, which is only worth it when failing
+        if parallel_build_path_iterator(&target_paths, &walk_filters, walk_monitor)?.is_empty() {
+            return Err(UndeadError::NoPythonFiles.into());
+        }
+        return Err(UndeadError::RootNotFound.into());
+    };
+    let import_roots: Vec<PathBuf> = import_roots.into_iter().map(|root| root.path).collect();
+
+    let mut walk_roots = import_roots.clone();
+    walk_roots.extend(
+        target_paths
+            .iter()
+            .filter(|target| !import_roots.iter().any(|root| target.starts_with(root)))
+            .cloned(),
+    );
+    let mut all_files =
+        parallel_build_path_iterator(&walk_roots, &WalkFilters::default(), walk_monitor)?;
+    // the walk of a root skips hidden and gitignored directories, which are still searched when
+    // given as targets
+    let skipped_targets: Vec<PathBuf> = target_paths
+        .iter()
+        .filter(|target| {
+            target.is_dir()
+                && !walk_roots.contains(target)
+                && !all_files.iter().any(|file| file.starts_with(target))
+        })
+        .cloned()
+        .collect();
+    if !skipped_targets.is_empty() {
+        all_files.extend(parallel_build_path_iterator(
+            &skipped_targets,
+            &WalkFilters::default(),
+            walk_monitor,
+        )?);
+        all_files.sort();
+    }
+    // roots may be nested inside one another
+    all_files.dedup();
+    let mut target_files: Vec<PathBuf> = all_files
+        .iter()
+        .filter(|file| {
+            target_paths.iter().any(|target| {
+                file.starts_with(target) && !walk_filters.is_ignored_below(file, target)
+            })
+        })
+        .cloned()
+        .collect();
+    // files given explicitly are searched whatever their extension or ignore rules, as walks
+    // always yield their roots
+    let explicit_files: Vec<PathBuf> = target_paths
+        .iter()
+        .filter(|target| target.is_file() && all_files.binary_search(target).is_err())
+        .cloned()
+        .collect();
+    target_files.extend(explicit_files.iter().cloned());
+    target_files.sort();
+    target_files.dedup();
+    all_files.extend(explicit_files);
+    all_files.sort();
+    all_files.dedup();
+    if target_files.is_empty() {
+        return Err(UndeadError::NoPythonFiles.into());
+    }
+    Ok(WalkedProject {
+        target_files,
+        all_files,
+        import_roots,
+    })
+}
+
+/// Gathers the settings deciding which files are entrypoints.
+fn entrypoint_options(cli: &Cli, import_roots: &[PathBuf]) -> anyhow::Result<EntrypointOptions> {
+    let mut scripts_dirs = resolve_paths(cli.scripts_dirs.clone())?;
+    if !cli.no_default_scripts_dirs {
+        for import_root in import_roots {
+            scripts_dirs.extend(
+                entrypoints::DEFAULT_SCRIPTS_DIRS
+                    .iter()
+                    .map(|dir| import_root.join(dir)),
+            );
+        }
+    }
+    Ok(EntrypointOptions {
+        scripts_dirs,
+        shebangs: !cli.no_shebang_entrypoints,
+        asyncio_run: cli.asyncio_run_entrypoints,
+    })
+}
+
+/// Collects the project roots imports get resolved against: the main python root first, followed
+/// by the roots of any target living outside of it.
+fn collect_import_roots(
+    python_root: &Path,
+    target_paths: &[PathBuf],
+    root_detection: Option<RootDetection>,
+) -> Vec<PathBuf> {
+    let outside_targets: Vec<PathBuf> = target_paths
+        .iter()
+        .filter(|target_path| !target_path.starts_with(python_root))
+        .cloned()
+        .collect();
+    let mut import_roots = vec![python_root.to_path_buf()];
+    for (root, _) in group_targets_by_root(&outside_targets, root_detection) {
+        if !import_roots.contains(&root) {
+            import_roots.push(root);
+        }
+    }
+    import_roots
+}
+
+/// Detects the project root of every target in parallel, grouping targets by root in the order
+/// roots are first met. Targets without a root are left out.
+fn group_targets_by_root(
+    target_paths: &[PathBuf],
+    root_detection: Option<RootDetection>,
+) -> Vec<(PathBuf, Vec<PathBuf>)> {
+    let roots: Vec<Option<PathBuf>> = target_paths
+        .par_iter()
+        .map(|target_path| detect_project_root(target_path, root_detection))
+        .collect();
+    let mut groups: Vec<(PathBuf, Vec<PathBuf>)> = Vec::new();
+    let mut group_indices: HashMap<PathBuf, usize> = HashMap::new();
+    for (target_path, root) in target_paths.iter().zip(roots) {
+        let Some(root) = root else {
+            continue;
+        };
+        let index = *group_indices.entry(root.clone()).or_insert_with(|| {
+            groups.push((root, Vec::new()));
+            groups.len() - 1
+        });
+        groups[index].1.push(target_path.clone());
+    }
+    groups
+}
+
+/// Returns the innermost import root containing `path`, defaulting to the main python root.
+fn import_root_for<'a>(path: &Path, import_roots: &'a [PathBuf]) -> &'a Path {
+    import_roots
+        .iter()
+        .filter(|root| path.starts_with(root))
+        .max_by_key(|root| root.components().count())
+        .unwrap_or(&import_roots[0])
+}
+
+/// Gathers the paths given on the command line along with the ones listed in `--paths-from`.
+fn collect_target_paths(cli: &Cli) -> anyhow::Result<Vec<PathBuf>> {
+    let mut paths = cli.paths.clone();
+    if let Some(paths_file) = &cli.paths_from {
+        let contents = std::fs::read_to_string(paths_file)?;
+        paths.extend(
+            contents
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(PathBuf::from),
+        );
+    }
+    Ok(paths)
+}
+
+/// Makes the given paths absolute, failing on the first one that doesn't exist.
+fn resolve_paths(paths: Vec<PathBuf>) -> anyhow::Result<Vec<PathBuf>> {
+    paths
+        .into_iter()
+        .map(|path| match path.canonicalize() {
+            Ok(resolved) => Ok(strip_extended_length_prefix(resolved)),
+            // not wrapped as context, which would have the path dropped along with it when
+            // converting to `UndeadError::Io`
+            Err(error) => Err(anyhow::anyhow!("{}: {}", path.display(), error)),
+        })
+        .collect()
+}
+
+/// On Windows, `canonicalize` returns extended-length paths (`\\?\C:\...`,
+/// `\\?\UNC\server\share\...`) which would otherwise leak into module names and hyperlinks.
+fn strip_extended_length_prefix(path: PathBuf) -> PathBuf {
+    if cfg!(windows) {
+        if let Some(path_str) = path.to_str() {
+            if let Some(unc) = path_str.strip_prefix(r"\\?\UNC\") {
+                return PathBuf::from(format!(r"\\{}", unc));
+            }
+            if let Some(stripped) = path_str.strip_prefix(r"\\?\") {
+                return PathBuf::from(stripped);
+            }
+        }
+    }
+    path
+}
+
+static PYTHON_INIT_FILE: &str = "__init__.py";
+static PYTHON_INIT_MODULE: &str = "__init__";
+static PYTHON_EXTENSION: &str = ".py";
+/// suffixes of the stems of files generated by protobuf, see `link_generated_pairs`
+static PB2_SUFFIX: &str = "_pb2";
+static GRPC_SUFFIX: &str = "_grpc";
+
+/// Renders an import as the module it executes (`pkg.__init__` for a package).
+fn resolve_import(import: Import) -> String {
+    match import {
+        Import::Module(module) | Import::NamespacePackage(module) => module,
+        Import::Package(mut package) => {
+            package.push('.');
+            package.push_str(PYTHON_INIT_MODULE);
+            package
+        }
+    }
+}
+
+/// Maps every imported module to the files importing it.
+fn index_imports(imports: Vec<(PathBuf, Import)>) -> HashMap<String, HashSet<PathBuf>> {
+    let mut import_index: HashMap<String, HashSet<PathBuf>> = HashMap::new();
+    for (importer, import) in imports {
+        let module = resolve_import(import);
+        // importing `a.b.c` runs the inits of packages `a` and `a.b` as well
+        let segments: Vec<&str> = module.split('.').collect();
+        for depth in 1..segments.len() {
+            let package_init = format!("{}.{}", segments[..depth].join("."), PYTHON_INIT_MODULE);
+            if package_init != module {
+                import_index
+                    .entry(package_init)
+                    .or_default()
+                    .insert(importer.clone());
+            }
+        }
+        import_index.entry(module).or_default().insert(importer);
+    }
+    import_index
+}
+
+/// Tells whether the file at `path` belongs to a test suite, judging from pytest's naming
+/// conventions and `test`/`tests` directories under its import root.
+fn is_test_file(path: &Path, import_root: &Path) -> bool {
+    let relative_path = path.strip_prefix(import_root).unwrap_or(path);
+    let is_test_dir = |component: &Component| matches!(component, Component::Normal(name) if *name == "tests" || *name == "test");
+    if let Some(parent) = relative_path.parent() {
+        if parent.components().any(|component| is_test_dir(&component)) {
+            return true;
+        }
+    }
+    let stem = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("");
+    stem.starts_with("test_") || stem.ends_with("_test") || stem == "conftest"
+}
+
+/// Merges the imports of modules under an import root nested in another one, e.g. `src` in a
+/// src-layout, made through the outer root (`import src.pkg`) into those made through the nested
+/// root (`import pkg`), which is what the files are named after.
+fn alias_nested_roots(
+    import_index: &mut HashMap<String, HashSet<PathBuf>>,
+    import_roots: &[PathBuf],
+) {
+    for nested_root in import_roots {
+        let Some(outer_root) = import_roots
+            .iter()
+            .find(|root| *root != nested_root && nested_root.starts_with(root))
+        else {
+            continue;
+        };
+        let prefix = format!("{}.", render_as_import_string(nested_root, outer_root));
+        let aliased_modules: Vec<String> = import_index
+            .keys()
+            .filter(|module| module.starts_with(&prefix))
+            .cloned()
+            .collect();
+        for module in aliased_modules {
+            let importers = import_index.remove(&module).unwrap_or_default();
+            import_index
+                .entry(module[prefix.len()..].to_string())
+                .or_default()
+                .extend(importers);
+        }
+    }
+}
+
+/// Where to cache extracted imports, if caching is enabled.
+fn cache_file_path(cli: &Cli, python_root: &Path) -> Option<PathBuf> {
+    let cache_dir = match &cli.cache_dir {
+        Some(cache_dir) => cache_dir.clone(),
+        // pull request checks run again and again on mostly unchanged files
+        None if cli.cache || cli.pr_mode.is_some() => cache::default_cache_dir(
+            python_root,
+            std::env::var_os("XDG_CACHE_HOME").map(PathBuf::from),
+        ),
+        None => return None,
+    };
+    Some(ImportCache::file_path(&cache_dir, python_root))
+}
+
+/// The imports found in a set of files.
+struct CompiledImports {
+    /// every import along with the file it was found in
+    imports: Vec<(PathBuf, Import)>,
+    /// the most elusive way any file loads modules dynamically
+    dynamic_import: Option<DynamicImport>,
+    /// package inits building `__all__` dynamically
+    dynamic_all_inits: Vec<PathBuf>,
+    /// constructs whose imports can't be worked out statically, with the file using them
+    unresolved: Vec<(PathBuf, String)>,
+    /// problems with files that don't prevent analyzing them
+    warnings: Vec<String>,
+    /// files that couldn't be read or parsed, with the reason why
+    failures: Vec<(PathBuf, String)>,
+    /// what tells whether each analyzed file is an entrypoint
+    entrypoint_markers: HashMap<PathBuf, EntrypointMarkers>,
+    /// hashes of the contents of the files that aren't blank
+    content_hashes: Vec<(PathBuf, u64)>,
+}
+
+/// Extracts the imports of every file, along with the file they were found in.
+///
+/// Files unchanged since they were put in `cache` aren't parsed again, the others get (re)cached.
+fn compile_imports(
+    python_files: &[PathBuf],
+    import_roots: &[PathBuf],
+    options: &ImportOptions,
+    mut cache: Option<&mut ImportCache>,
+) -> anyhow::Result<CompiledImports> {
+    let imports_queue = SegQueue::<(PathBuf, Import)>::new();
+    let markers_queue = SegQueue::<(PathBuf, EntrypointMarkers)>::new();
+    let hashes_queue = SegQueue::<(PathBuf, u64)>::new();
+    let dynamic_imports_queue = SegQueue::<DynamicImport>::new();
+    let dynamic_all_queue = SegQueue::<PathBuf>::new();
+    let unresolved_queue = SegQueue::<(PathBuf, String)>::new();
+    let warnings_queue = SegQueue::<String>::new();
+    let failures_queue = SegQueue::<(PathBuf, String)>::new();
+    let fresh_entries = SegQueue::<(PathBuf, FileAnalysis)>::new();
+    let cached = cache.as_deref();
+    python_files.par_iter().for_each(|path| {
+        let analysis = match cached.and_then(|cache| cache.get(path)) {
+            Some(analysis) => analysis.clone(),
+            None => match analyze_file(path, import_root_for(path, import_roots), options) {
+                Ok(analysis) => {
+                    if cached.is_some() {
+                        fresh_entries.push((path.clone(), analysis.clone()));
+                    }
+                    analysis
+                }
+                Err(error) => {
+                    failures_queue.push((path.clone(), error.to_string()));
+                    return;
+                }
+            },
+        };
+        if let Some(dynamic_import) = analysis.dynamic_import {
+            dynamic_imports_queue.push(dynamic_import);
+        }
+        if analysis.dynamic_all {
+            dynamic_all_queue.push(path.clone());
+        }
+        analysis
+            .unresolved
+            .into_iter()
+            .for_each(|construct| unresolved_queue.push((path.clone(), construct)));
+        markers_queue.push((path.clone(), analysis.entrypoint_markers));
+        if let Some(content_hash) = analysis.content_hash {
+            hashes_queue.push((path.clone(), content_hash));
+        }
+        analysis
+            .warnings
+            .into_iter()
+            .for_each(|warning| warnings_queue.push(warning));
+        analysis
+            .imports
+            .into_iter()
+            .for_each(|import| imports_queue.push((path.clone(), import)));
+    });
+
+    if let Some(cache) = cache.as_mut() {
+        fresh_entries
+            .into_iter()
+            .for_each(|(path, analysis)| cache.insert(path, analysis));
+    }
+    Ok(CompiledImports {
+        imports: imports_queue.into_iter().collect(),
+        dynamic_import: dynamic_imports_queue.into_iter().max(),
+        dynamic_all_inits: dynamic_all_queue.into_iter().collect(),
+        unresolved: unresolved_queue.into_iter().collect(),
+        warnings: warnings_queue.into_iter().collect(),
+        failures: failures_queue.into_iter().collect(),
+        entrypoint_markers: markers_queue.into_iter().collect(),
+        content_hashes: hashes_queue.into_iter().collect(),
+    })
+}
+
+/// A project module or package reached by an import statement, as a dotted name.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub enum Import {
+    Module(String),
+    Package(String),
+    /// a package without `__init__.py` (PEP 420), made of its submodules only
+    NamespacePackage(String),
+}
+impl Import {
+    /// The import of the package `name` living in `dir`, which may be a namespace package.
+    fn package(dir: &Path, name: String) -> Import {
+        if dir.join(PYTHON_INIT_FILE).is_file() {
+            Import::Package(name)
+        } else {
+            Import::NamespacePackage(name)
+        }
+    }
+
+    fn from_import(
+        import: &StmtImport,
+        python_root: &Path,
+        namespace_roots: &[PathBuf],
+    ) -> Vec<Import> {
+        import
+            .names
+            .iter()
+            .map(|alias| {
+                let alias_name = alias.name.to_string();
+                let root = namespace_root_for(&alias_name, python_root, namespace_roots);
+                let full_path = root.join(alias_name.replace('.', MAIN_SEPARATOR_STR));
+                if full_path.is_dir() {
+                    Import::package(&full_path, alias_name)
+                } else {
+                    Import::Module(alias_name)
+                }
+            })
+            .collect()
+    }
+
+    /// Relative imports going above the filesystem root import nothing, `ImportVisitor` warning
+    /// about them.
+    fn from_import_from(
+        import_from: &StmtImportFrom,
+        current_file_path: &Path,
+        python_root: &Path,
+        absolute_import_base: &Path,
+    ) -> Vec<Import> {
+        let Some(full_import_path) =
+            Self::import_from_path(import_from, current_file_path, absolute_import_base)
+        else {
+            return vec![];
+        };
+        if import_from.module.is_some() {
+            // names imported from a module file are symbols, not submodules
+            if full_import_path.with_extension("py").is_file() {
+                return vec![Import::Module(render_as_import_string(
+                    &full_import_path,
+                    python_root,
+                ))];
+            }
+        }
+        let is_package = full_import_path.is_dir() && full_import_path != python_root;
+        if is_package
+            && import_from
+                .names
+                .iter()
+                .any(|alias| alias.name.as_str() == "*")
+        {
+            return star_imports(&full_import_path, python_root);
+        }
+        import_from
+            .names
+            .iter()
+            .map(|alias| {
+                let alias_name = alias.name.to_string();
+                let final_import_path = full_import_path.join(alias_name);
+                let final_import = render_as_import_string(&final_import_path, python_root);
+                if final_import_path.is_dir() {
+                    Import::package(&final_import_path, final_import)
+                } else if is_package && !final_import_path.with_extension("py").is_file() {
+                    // not a submodule: a name defined in the package's `__init__.py`
+                    Import::package(
+                        &full_import_path,
+                        render_as_import_string(&full_import_path, python_root),
+                    )
+                } else {
+                    Import::Module(final_import)
+                }
+            })
+            .collect()
+    }
+
+    /// The path `from ... import` statements import from, without extension, or `None` for
+    /// relative imports going above the filesystem root.
+    fn import_from_path(
+        import_from: &StmtImportFrom,
+        current_file_path: &Path,
+        absolute_import_base: &Path,
+    ) -> Option<PathBuf> {
+        let base_import_path: PathBuf;
+        match import_from.level {
+            Some(level) => {
+                // absolute import
+                if level.to_usize() == 0 {
+                    base_import_path = absolute_import_base.to_path_buf();
+                // relative import
+                } else {
+                    base_import_path = current_file_path
+                        .ancestors()
+                        .nth(level.to_usize())?
+                        .to_path_buf();
+                }
+            }
+            // when does this happen?
+            None => {
+                base_import_path = absolute_import_base.to_path_buf();
+            }
+        }
+        Some(match import_from.module.as_ref() {
+            Some(module) => {
+                base_import_path.join(module.to_string().replace('.', MAIN_SEPARATOR_STR))
+            }
+            None => base_import_path,
+        })
+    }
+}
+
+/// The imports of `from package import *`: the package init, and the submodules it exports through
+/// `__all__`, or all of them when it doesn't declare them statically.
+fn star_imports(package_dir: &Path, python_root: &Path) -> Vec<Import> {
+    let submodules = match static_all(&package_dir.join(PYTHON_INIT_FILE)) {
+        Some(names) => names.iter().map(|name| package_dir.join(name)).collect(),
+        None => package_children(package_dir),
+    };
+    std::iter::once(Import::package(
+        package_dir,
+        render_as_import_string(package_dir, python_root),
+    ))
+    .chain(
+        submodules
+            .iter()
+            .map(|submodule| submodule_import(submodule, python_root)),
+    )
+    .collect()
+}
+
+/// The names the module at `path` lists in `__all__`, if it sets it to static lists only.
+fn static_all(path: &Path) -> Option<Vec<String>> {
+    let contents = std::fs::read_to_string(path).ok()?;
+    let Ok(Mod::Module(ModModule { body, .. })) = parse(&contents, Mode::Module, "<embedded>")
+    else {
+        return None;
+    };
+    let is_all =
+        |target: &Expr| matches!(target, Expr::Name(name) if name.id.as_str() == "__all__");
+    let mut names = None;
+    for stmt in body.iter() {
+        match stmt {
+            Stmt::Assign(assign) if assign.targets.iter().any(is_all) => {
+                names = Some(static_names(&assign.value)?);
+            }
+            Stmt::AugAssign(assign) if is_all(&assign.target) => {
+                names
+                    .get_or_insert_with(Vec::new)
+                    .extend(static_names(&assign.value)?);
+            }
+            _ => {}
+        }
+    }
+    names
+}
+
+/// The direct submodules and subpackages of the package in `package_dir`, without extensions.
+fn package_children(package_dir: &Path) -> Vec<PathBuf> {
+    let mut children: Vec<PathBuf> = std::fs::read_dir(package_dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter_map(|path| match path.extension() {
+            Some(extension) if extension == "py" => Some(path.with_extension("")),
+            _ if path.is_dir() => Some(path),
+            _ => None,
+        })
+        .filter(|path| !path.ends_with("__init__"))
+        .collect();
+    children.sort();
+    children
+}
+
+/// The import of the submodule or subpackage at `path`, given without extension.
+fn submodule_import(path: &Path, python_root: &Path) -> Import {
+    if path.is_dir() {
+        Import::package(path, render_as_import_string(path, python_root))
+    } else {
+        Import::Module(render_as_import_string(
+            &path.with_extension("py"),
+            python_root,
+        ))
+    }
+}
+
+/// Counts the lines of the file at `path`, if it can be read.
+pub(crate) fn count_lines(path: &Path) -> Option<usize> {
+    std::fs::read_to_string(path)
+        .ok()
+        .map(|contents| contents.lines().count())
+}
+
+/// Renders the path of a python file as the dotted module name it is imported with.
+pub fn render_as_import_string(path: &Path, python_root: &Path) -> String {
+    let mut prefix = python_root.to_string_lossy().to_string();
+    prefix.push_str(MAIN_SEPARATOR_STR);
+    let mut result = path.to_string_lossy().to_string();
+    result = result.strip_prefix(&prefix).unwrap_or(&result).to_string();
+    result = result
+        .strip_suffix(PYTHON_EXTENSION)
+        .unwrap_or(&result)
+        .to_string();
+    result.to_string().replace(MAIN_SEPARATOR_STR, ".")
+}
+
+/// Settings affecting how imports are extracted from python files.
+#[derive(Debug, Clone, Default)]
+pub struct ImportOptions {
+    /// directory absolute `from` imports are resolved against instead of the python root
+    pub relative_import_base: Option<PathBuf>,
+    /// directories sharing a single import namespace with the python root
+    pub namespace_roots: Vec<PathBuf>,
+    pub parse_mode: ParseMode,
+    /// whether to leave out imports only made for type checkers, under `if TYPE_CHECKING:`
+    pub ignore_type_checking_imports: bool,
+}
+
+/// How python files are parsed.
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ParseMode {
+    /// regular python modules
+    #[default]
+    Module,
+    /// statements as typed in an interactive session (e.g. notebook cells)
+    Interactive,
+}
+
+/// A way of loading modules that import statements don't reveal, from the least to the most
+/// likely to hide usages of project files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum DynamicImport {
+    /// `getattr` called on an imported module
+    Getattr,
+    /// `importlib` imported, or `__import__` called
+    Importlib,
+}
+
+/// What a python file reveals about the modules it loads.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FileAnalysis {
+    pub imports: Vec<Import>,
+    /// the most elusive way the file loads modules dynamically, if any
+    pub dynamic_import: Option<DynamicImport>,
+    /// whether the file is a package init building `__all__` dynamically, making all of the
+    /// package's submodules potentially used
+    pub dynamic_all: bool,
+    /// constructs loading modules in ways that can't be worked out statically, for `--strict`
+    pub unresolved: Vec<String>,
+    /// problems with the file that don't prevent analyzing it
+    pub warnings: Vec<String>,
+    pub entrypoint_markers: EntrypointMarkers,
+    /// hash of the contents of the file, unless it is blank, to find duplicates
+    pub content_hash: Option<u64>,
+    /// inits of the packages star imports were expanded from, whose `__all__` the imports depend on
+    pub star_import_inits: Vec<PathBuf>,
+}
+
+/// Parses the python file at `path` and extracts the imports it contains.
+pub fn extract_imports(
+    path: &Path,
+    python_root: &Path,
+    options: &ImportOptions,
+) -> Result<Vec<Import>, UndeadError> {
+    Ok(analyze_file(path, python_root, options)?.imports)
+}
+
+/// Hashes the contents of a file, leaving out blank ones (e.g. most package inits), which are
+/// identical without being copies.
+fn content_hash(contents: &str) -> Option<u64> {
+    if contents.trim().is_empty() {
+        return None;
+    }
+    let mut hasher = DefaultHasher::new();
+    contents.hash(&mut hasher);
+    Some(hasher.finish())
+}
+
+/// Groups the files among `paths` that have the same contents, sorted.
+fn find_duplicates(paths: &[PathBuf], content_hashes: &[(PathBuf, u64)]) -> Vec<Vec<PathBuf>> {
+    let paths: HashSet<&PathBuf> = paths.iter().collect();
+    let mut groups: HashMap<u64, Vec<PathBuf>> = HashMap::new();
+    for (path, content_hash) in content_hashes {
+        if paths.contains(path) {
+            groups.entry(*content_hash).or_default().push(path.clone());
+        }
+    }
+    let mut duplicates: Vec<Vec<PathBuf>> = groups
+        .into_values()
+        .filter(|group| group.len() > 1)
+        .map(|mut group| {
+            group.sort();
+            group
+        })
+        .collect();
+    duplicates.sort();
+    duplicates
+}
+
+/// Parses the python file at `path` and extracts the imports it contains, along with any sign of
+/// dynamic imports and of being an entrypoint.
+pub fn analyze_file(
+    path: &Path,
+    python_root: &Path,
+    options: &ImportOptions,
+) -> Result<FileAnalysis, UndeadError> {
+    // read once for all of the analysis, entrypoint markers included
+    let file_contents = std::fs::read_to_string(path)?;
+    let mode = match options.parse_mode {
+        ParseMode::Module => Mode::Module,
+        ParseMode::Interactive => Mode::Interactive,
+    };
+    match parse(&file_contents, mode, "<embedded>") {
+        Ok(Mod::Module(ModModule { body, .. }))
+        | Ok(Mod::Interactive(ModInteractive { body, .. })) => {
+            let mut visitor = ImportVisitor::new(path, python_root, options);
+            // it seems rustpython's asts don't implement accept
+            body.iter()
+                .for_each(|stmt| visitor.visit_stmt(stmt.clone()));
+            // a module `__getattr__` can return anything, submodules included
+            if body.iter().any(|stmt| {
+                matches!(stmt, Stmt::FunctionDef(function) if function.name.as_str() == "__getattr__")
+            }) {
+                visitor
+                    .unresolved
+                    .push("module-level `__getattr__`".to_string());
+            }
+            if visitor.dynamic_all {
+                visitor
+                    .unresolved
+                    .push("`__all__` built dynamically".to_string());
+            }
+            Ok(FileAnalysis {
+                imports: visitor.imports,
+                dynamic_import: visitor.dynamic_import,
+                dynamic_all: visitor.dynamic_all,
+                unresolved: visitor.unresolved,
+                warnings: visitor.warnings,
+                entrypoint_markers: EntrypointMarkers::scan(&file_contents),
+                content_hash: content_hash(&file_contents),
+                star_import_inits: visitor.star_import_inits,
+            })
+        }
+        Ok(_) => Err(UndeadError::ParseFailure {
+            path: path.to_path_buf(),
+            message: format!("Error parsing file: {:?}", path),
+        }),
+        Err(error) => Err(UndeadError::ParseFailure {
+            path: path.to_path_buf(),
+            message: format!("invalid syntax: {}", error.error),
+        }),
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ImportVisitor {
+    pub imports: Vec<Import>,
+    pub python_root: PathBuf,
+    pub absolute_import_base: PathBuf,
+    pub current_file_path: PathBuf,
+    pub namespace_roots: Vec<PathBuf>,
+    /// names imports bound in the file, which may refer to modules
+    pub bound_modules: HashSet<String>,
+    /// names `importlib.resources` functions were imported as
+    pub resource_functions: HashSet<String>,
+    pub dynamic_import: Option<DynamicImport>,
+    pub dynamic_all: bool,
+    pub unresolved: Vec<String>,
+    pub ignore_type_checking_imports: bool,
+    /// whether the statements being visited only run for type checkers
+    pub in_type_checking_block: bool,
+    pub warnings: Vec<String>,
+    /// inits of the packages star imports were expanded from
+    pub star_import_inits: Vec<PathBuf>,
+}
+
+/// Relative import levels beyond this are shown as a count in warnings.
+static MAX_SPELLED_OUT_LEVEL: usize = 16;
+
+/// `importlib.resources` functions taking the package holding the resources as first argument.
+static RESOURCE_FUNCTIONS: &[&str] = &[
+    "files",
+    "read_text",
+    "read_binary",
+    "open_text",
+    "open_binary",
+    "path",
+];
+
+impl ImportVisitor {
+    fn new(path: &Path, python_root: &Path, options: &ImportOptions) -> ImportVisitor {
+        ImportVisitor {
+            imports: vec![],
+            python_root: python_root.to_path_buf(),
+            absolute_import_base: options
+                .relative_import_base
+                .clone()
+                .unwrap_or_else(|| python_root.to_path_buf()),
+            current_file_path: path.to_path_buf(),
+            namespace_roots: options.namespace_roots.clone(),
+            bound_modules: HashSet::new(),
+            resource_functions: HashSet::new(),
+            dynamic_import: None,
+            dynamic_all: false,
+            unresolved: vec![],
+            ignore_type_checking_imports: options.ignore_type_checking_imports,
+            in_type_checking_block: false,
+            warnings: vec![],
+            star_import_inits: vec![],
+        }
+    }
+
+    fn record_dynamic_import(&mut self, dynamic_import: DynamicImport) {
+        self.dynamic_import = self.dynamic_import.max(Some(dynamic_import));
+    }
+
+    /// Considers the submodules a package init exports through `__all__` as imported. When
+    /// `__all__` isn't a static list of names, any submodule could be exported, so all of them are.
+    fn record_all(&mut self, target: &Expr, value: &Expr) {
+        let is_all = matches!(target, Expr::Name(name) if name.id.as_str() == "__all__");
+        let is_init = self
+            .current_file_path
+            .file_name()
+            .is_some_and(|file_name| file_name == PYTHON_INIT_FILE);
+        let Some(package_dir) = self
+            .current_file_path
+            .parent()
+            .filter(|_| is_all && is_init)
+        else {
+            return;
+        };
+        let submodules: Vec<PathBuf> = match static_names(value) {
+            Some(names) => names.iter().map(|name| package_dir.join(name)).collect(),
+            None => {
+                self.dynamic_all = true;
+                package_children(package_dir)
+            }
+        };
+        // names that aren't submodules are defined in the init, and match no file anyway
+        for submodule in submodules {
+            self.imports
+                .push(submodule_import(&submodule, &self.python_root));
+        }
+    }
+
+    /// The package `call` gets resources from when it calls an `importlib.resources` function
+    /// with a literal package name, e.g. `importlib.resources.files("pkg")`.
+    fn resource_package(&self, call: &ExprCall) -> Option<String> {
+        let is_resource_function = match call.func.as_ref() {
+            Expr::Attribute(attribute) => {
+                RESOURCE_FUNCTIONS.contains(&attribute.attr.as_str())
+                    && is_resources_module(&attribute.value)
+            }
+            Expr::Name(function) => self.resource_functions.contains(function.id.as_str()),
+            _ => false,
+        };
+        match call.args.first() {
+            Some(Expr::Constant(constant)) if is_resource_function => match &constant.value {
+                Constant::Str(package) => Some(package.clone()),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Tells whether `test` is `TYPE_CHECKING` or `typing.TYPE_CHECKING`, which only type checkers
+/// consider true.
+fn is_type_checking(test: &Expr) -> bool {
+    match test {
+        Expr::Name(name) => name.id.as_str() == "TYPE_CHECKING",
+        Expr::Attribute(attribute) => {
+            attribute.attr.as_str() == "TYPE_CHECKING"
+                && matches!(
+                    attribute.value.as_ref(),
+                    Expr::Name(module) if module.id.as_str() == "typing"
+                )
+        }
+        _ => false,
+    }
+}
+
+/// Tells whether `module` refers to `importlib.resources` (or its `importlib_resources` backport).
+fn is_resources_module(module: &Expr) -> bool {
+    match module {
+        Expr::Name(name) => ["resources", "importlib_resources"].contains(&name.id.as_str()),
+        Expr::Attribute(attribute) => {
+            attribute.attr.as_str() == "resources"
+                && matches!(
+                    attribute.value.as_ref(),
+                    Expr::Name(parent) if parent.id.as_str() == "importlib"
+                )
+        }
+        _ => false,
+    }
+}
+
+/// The names listed by a list or tuple of string literals, or `None` for any other expression.
+fn static_names(value: &Expr) -> Option<Vec<String>> {
+    let elements = match value {
+        Expr::List(list) => &list.elts,
+        Expr::Tuple(tuple) => &tuple.elts,
+        _ => return None,
+    };
+    elements
+        .iter()
+        .map(|element| match element {
+            Expr::Constant(constant) => match &constant.value {
+                Constant::Str(name) => Some(name.clone()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect()
+}
+
+fn is_string_literal(expr: &Expr) -> bool {
+    matches!(expr, Expr::Constant(constant) if matches!(constant.value, Constant::Str(_)))
+}
+
+/// Finds the root an absolute import of `module` resolves against: the python root, unless only one
+/// of the namespace roots contains its top-level package.
+fn namespace_root_for<'a>(
+    module: &str,
+    python_root: &'a Path,
+    namespace_roots: &'a [PathBuf],
+) -> &'a Path {
+    let top_level = module.split('.').next().unwrap_or(module);
+    std::iter::once(python_root)
+        .chain(namespace_roots.iter().map(PathBuf::as_path))
+        .find(|root| {
+            root.join(top_level).is_dir() || root.join(top_level).with_extension("py").is_file()
+        })
+        .unwrap_or(python_root)
+}
+
+impl Visitor for ImportVisitor {
+    fn visit_stmt_if(&mut self, stmt: StmtIf) {
+        if !self.ignore_type_checking_imports || !is_type_checking(&stmt.test) {
+            return self.generic_visit_stmt_if(stmt);
+        }
+        let in_type_checking_block = std::mem::replace(&mut self.in_type_checking_block, true);
+        stmt.body.into_iter().for_each(|stmt| self.visit_stmt(stmt));
+        self.in_type_checking_block = in_type_checking_block;
+        stmt.orelse
+            .into_iter()
+            .for_each(|stmt| self.visit_stmt(stmt));
+    }
+
+    fn visit_stmt_assign(&mut self, stmt: StmtAssign) {
+        for target in stmt.targets.iter() {
+            self.record_all(target, &stmt.value);
+        }
+        self.generic_visit_stmt_assign(stmt);
+    }
+
+    fn visit_stmt_aug_assign(&mut self, stmt: StmtAugAssign) {
+        self.record_all(&stmt.target, &stmt.value);
+        self.generic_visit_stmt_aug_assign(stmt);
+    }
+
+    fn visit_stmt_ann_assign(&mut self, stmt: StmtAnnAssign) {
+        if let Some(value) = stmt.value.as_ref() {
+            self.record_all(&stmt.target, value);
+        }
+        self.generic_visit_stmt_ann_assign(stmt);
+    }
+
+    fn visit_expr_call(&mut self, call: ExprCall) {
+        let function_name = match call.func.as_ref() {
+            Expr::Name(function) => Some(function.id.as_str()),
+            Expr::Attribute(attribute) => Some(attribute.attr.as_str()),
+            _ => None,
+        };
+        if matches!(function_name, Some("__import__" | "import_module"))
+            && !call.args.first().is_some_and(is_string_literal)
+        {
+            self.unresolved.push(format!(
+                "`{}` called with a non-literal module",
+                function_name.unwrap_or_default()
+            ));
+        }
+        if let Expr::Name(function) = call.func.as_ref() {
+            match function.id.as_str() {
+                "__import__" => self.record_dynamic_import(DynamicImport::Importlib),
+                "getattr" => {
+                    if let Some(Expr::Name(target)) = call.args.first() {
+                        if self.bound_modules.contains(target.id.as_str()) {
+                            self.record_dynamic_import(DynamicImport::Getattr);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        // the package holding resources is used, even if nothing imports it
+        if let Some(package) = self.resource_package(&call) {
+            self.imports.push(Import::Package(package));
+        }
+        self.generic_visit_expr_call(call);
+    }
+
+    fn visit_stmt_import(&mut self, stmt: StmtImport) {
+        if self.in_type_checking_block {
+            return;
+        }
+        for alias in stmt.names.iter() {
+            if alias.name.split('.').next() == Some("importlib") {
+                self.record_dynamic_import(DynamicImport::Importlib);
+            }
+            let bound_name = match &alias.asname {
+                Some(asname) => asname.to_string(),
+                None => alias.name.split('.').next().unwrap_or_default().to_string(),
+            };
+            self.bound_modules.insert(bound_name);
+        }
+        self.imports.extend(Import::from_import(
+            &stmt,
+            &self.python_root,
+            &self.namespace_roots,
+        ));
+    }
+
+    fn visit_stmt_import_from(&mut self, stmt: StmtImportFrom) {
+        if self.in_type_checking_block {
+            return;
+        }
+        if let Some(module) = stmt.module.as_ref() {
+            if module.split('.').next() == Some("importlib") {
+                self.record_dynamic_import(DynamicImport::Importlib);
+            }
+        }
+        let is_resources_module = stmt.module.as_ref().is_some_and(|module| {
+            ["importlib.resources", "importlib_resources"].contains(&module.as_str())
+        });
+        for alias in stmt.names.iter() {
+            let bound_name = alias.asname.as_ref().unwrap_or(&alias.name);
+            self.bound_modules.insert(bound_name.to_string());
+            if is_resources_module && RESOURCE_FUNCTIONS.contains(&alias.name.as_str()) {
+                self.resource_functions.insert(bound_name.to_string());
+            }
+        }
+        let level = stmt.level.map_or(0, |level| level.to_usize());
+        if level >= self.current_file_path.ancestors().count() {
+            // spelling out the dots of crafted levels would drown the warning
+            let dots = if level <= MAX_SPELLED_OUT_LEVEL {
+                ".".repeat(level)
+            } else {
+                format!("<{level} dots>")
+            };
+            self.warnings.push(format!(
+                "{}: `from {}{} import ...` goes above the filesystem root, ignoring it",
+                self.current_file_path.display(),
+                dots,
+                stmt.module.as_deref().unwrap_or_default()
+            ));
+            return;
+        }
+        let is_absolute = level == 0;
+        let (python_root, absolute_import_base) = match stmt.module.as_ref() {
+            // an explicit --relative-import-base takes precedence over namespace roots
+            Some(module) if is_absolute && self.absolute_import_base == self.python_root => {
+                let root =
+                    namespace_root_for(module.as_str(), &self.python_root, &self.namespace_roots);
+                (root, root)
+            }
+            _ => (
+                self.python_root.as_path(),
+                self.absolute_import_base.as_path(),
+            ),
+        };
+        let imports = Import::from_import_from(
+            &stmt,
+            &self.current_file_path,
+            python_root,
+            absolute_import_base,
+        );
+        let star_import_init = if stmt.names.iter().any(|alias| alias.name.as_str() == "*") {
+            Import::import_from_path(&stmt, &self.current_file_path, absolute_import_base)
+                .filter(|package_dir| package_dir.is_dir())
+                .map(|package_dir| package_dir.join(PYTHON_INIT_FILE))
+        } else {
+            None
+        };
+        self.imports.extend(imports);
+        self.star_import_inits.extend(star_import_init);
+    }
+}
+
+/// Entries to leave out when walking the filesystem.
+#[derive(Debug, Clone, Default)]
+struct WalkFilters {
+    ignore_globs: GlobSet,
+    ignore_regex: Option<RegexMatcher>,
+}
+
+impl WalkFilters {
+    /// Tells whether a walk of `root` would have left out `path`, along with any directory between
+    /// them.
+    fn is_ignored_below(&self, path: &Path, root: &Path) -> bool {
+        path.ancestors()
+            .take_while(|ancestor| *ancestor != root && ancestor.starts_with(root))
+            .any(|ancestor| self.is_ignored(ancestor))
+    }
+
+    fn is_ignored(&self, path: &Path) -> bool {
+        if self.ignore_globs.is_match(path) {
+            return true;
+        }
+        match &self.ignore_regex {
+            Some(regex) => regex
+                .is_match(path.to_string_lossy().as_bytes())
+                .unwrap_or(false),
+            None => false,
+        }
+    }
+}
+
+/// Compiles all `--ignore-globs` patterns into a single set, anchoring relative ones at any depth
+/// the way `.gitignore` does, so that `tests` ignores every `tests` directory.
+fn build_ignore_globs(patterns: &[String]) -> anyhow::Result<GlobSet> {
+    let mut globs = GlobSetBuilder::new();
+    for pattern in patterns {
+        let pattern = if Path::new(pattern).is_absolute() || pattern.starts_with("**/") {
+            pattern.clone()
+        } else {
+            format!("**/{pattern}")
+        };
+        globs.add(GlobBuilder::new(&pattern).literal_separator(true).build()?);
+    }
+    Ok(globs.build()?)
+}
+
+/// Compiles all `--ignore-regex` patterns into a single matcher matching any of them.
+fn build_ignore_regex(patterns: &[String]) -> anyhow::Result<Option<RegexMatcher>> {
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+    let alternation = patterns
+        .iter()
+        .map(|pattern| format!("(?:{})", pattern))
+        .collect::<Vec<String>>()
+        .join("|");
+    Ok(Some(RegexMatcher::new(&alternation)?))
+}
+
+/// Keeps an eye on filesystem walks: the errors they meet and how long they take.
+#[derive(Debug, Default)]
+struct WalkMonitor {
+    /// whether to only count errors instead of printing each of them
+    quiet: bool,
+    errors: Mutex<HashSet<String>>,
+    /// how long walks may take in total, along with when that time runs out
+    time_limit: Option<(Duration, Instant)>,
+    timed_out: AtomicBool,
+    /// how many threads walks use, 0 for the walker to decide
+    threads: usize,
+}
+
+impl WalkMonitor {
+    fn new(cli: &Cli) -> WalkMonitor {
+        WalkMonitor {
+            quiet: cli.quiet_errors,
+            threads: cli.jobs.map_or(0, NonZeroUsize::get),
+            time_limit: cli.max_walk_time.map(|seconds| {
+                let max_walk_time = Duration::from_secs(seconds);
+                (max_walk_time, Instant::now() + max_walk_time)
+            }),
+            ..Default::default()
+        }
+    }
+
+    /// Tells whether walks have run for too long, and should stop.
+    fn is_out_of_time(&self) -> bool {
+        let out_of_time = self
+            .time_limit
+            .is_some_and(|(_, deadline)| Instant::now() >= deadline);
+        if out_of_time {
+            self.timed_out.store(true, Ordering::Relaxed);
+        }
+        out_of_time
+    }
+
+    /// Fails if a walk was stopped for taking too long.
+    fn check_time_limit(&self) -> anyhow::Result<()> {
+        match self.time_limit {
+            Some((max_walk_time, _)) if self.timed_out.load(Ordering::Relaxed) => {
+                anyhow::bail!(
+                    "walking the filesystem took longer than {}s, aborting (is there a symlink \
+                     loop or a slow mount in the target paths?)",
+                    max_walk_time.as_secs()
+                )
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn report(&self, error: ignore::Error) {
+        let error = error.to_string();
+        if !self.quiet {
+            eprintln!("Error: {}", error);
+        }
+        self.errors.lock().unwrap().insert(error);
+    }
+
+    /// How many errors were held back, if any.
+    fn summary(&self) -> Option<String> {
+        match self.errors.lock().unwrap().len() {
+            0 => None,
+            _ if !self.quiet => None,
+            1 => Some("1 path couldn't be walked (hidden by --quiet-errors)".to_string()),
+            count => Some(format!(
+                "{count} paths couldn't be walked (hidden by --quiet-errors)"
+            )),
+        }
+    }
+}
+
+fn parallel_build_path_iterator(
+    paths: &[PathBuf],
+    walk_filters: &WalkFilters,
+    walk_monitor: &WalkMonitor,
+) -> anyhow::Result<Vec<PathBuf>> {
+    let mut walk_builder = walk_builder(paths, walk_filters);
+    walk_builder.threads(walk_monitor.threads);
+    let file_queue = SegQueue::<PathBuf>::new();
+    walk_builder.build_parallel().run(|| {
+        Box::new(
+            |entry: Result<DirEntry, ignore::Error>| -> ignore::WalkState {
+                if walk_monitor.is_out_of_time() {
+                    return ignore::WalkState::Quit;
+                }
+                match entry {
+                    Ok(entry) => {
+                        let file_type = entry.file_type().unwrap();
+                        if !file_type.is_dir() {
+                            file_queue.push(entry.path().to_path_buf());
+                        }
+                        ignore::WalkState::Continue
+                    }
+                    Err(err) => {
+                        walk_monitor.report(err);
+                        ignore::WalkState::Continue
+                    }
+                }
+            },
+        )
+    });
+    walk_monitor.check_time_limit()?;
+    // the walk is parallel, sort to keep results (and root detection) deterministic
+    let mut paths: Vec<PathBuf> = file_queue.into_iter().collect();
+    paths.sort();
+    Ok(paths)
+}
+
+fn walk_builder(paths: &[PathBuf], walk_filters: &WalkFilters) -> WalkBuilder {
+    let mut types_builder = TypesBuilder::new();
+    types_builder.add_defaults().select("python");
+
+    let mut walk_builder = WalkBuilder::new(&paths[0]);
+    for path in paths.iter().skip(1) {
+        walk_builder.add(path);
+    }
+    let walk_filters = walk_filters.clone();
+    walk_builder.filter_entry(move |entry| !walk_filters.is_ignored(entry.path()));
+    walk_builder.types(types_builder.build().unwrap());
+    walk_builder
+}
+
+/// Files marking the root directory of a python project.
+static PROJECT_FILE_MARKERS: &[&str] = &["setup.py", "pyproject.toml"];
+static GIT_MARKERS: &[&str] = &[".git"];
+static ANY_MARKERS: &[&str] = &["setup.py", "pyproject.toml", ".git"];
+
+fn is_python_project_root(dir: &Path, markers: &[&str]) -> bool {
+    for marker in markers {
+        if dir.join(marker).exists() {
+            return true;
+        }
+    }
+    false
+}
+
+/// Lists the modules of the console scripts declared in the `pyproject.toml` and `setup.py` of
+/// the project rooted at `root`.
+fn collect_entry_point_modules(root: &Path) -> Vec<String> {
+    let mut modules = std::fs::read_to_string(root.join("pyproject.toml"))
+        .map(|contents| pyproject::script_modules(&contents))
+        .unwrap_or_default();
+    modules.extend(SetupDeclarations::load(root).entry_point_modules);
+    modules
+}
+
+/// Finds the root of the project `path` belongs to using the given strategy, which defaults to
+/// looking for any marker.
+fn detect_project_root(path: &Path, root_detection: Option<RootDetection>) -> Option<PathBuf> {
+    let markers = match root_detection {
+        None => ANY_MARKERS,
+        Some(RootDetection::Markers) => PROJECT_FILE_MARKERS,
+        Some(RootDetection::Git) => GIT_MARKERS,
+        Some(RootDetection::None) => {
+            let dir = if path.is_file() { path.parent()? } else { path };
+            return Some(dir.to_path_buf());
+        }
+    };
+    find_python_project_root(path, markers).map(Path::to_path_buf)
+}
+
+/// Finds the root path of a Python project, the closest directory containing one of `markers`,
+/// starting from a given directory, or from the directory of a given file.
+fn find_python_project_root<'a>(start: &'a Path, markers: &[&str]) -> Option<&'a Path> {
+    let mut current_dir = if start.is_file() {
+        start.parent()?
+    } else {
+        start
+    };
+
+    loop {
+        if is_python_project_root(current_dir, markers) {
+            return Some(current_dir);
+        }
+
+        // Move to the parent directory
+        match current_dir.parent() {
+            Some(parent) => current_dir = parent,
+            None => break,
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use rustpython_ast::Stmt;
+    use std::fs;
+    use tempfile::TempDir;
+
+    /// Creates a temporary python project containing the given files.
+    fn create_project(files: &[(&str, &str)]) -> TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("pyproject.toml"), "").unwrap();
+        for (path, contents) in files {
+            let path = dir.path().join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+        dir
+    }
+
+    fn cli_for(dir: &TempDir, args: &[&str]) -> Cli {
+        let root = dir.path().to_str().unwrap();
+        Cli::parse_from(["undead", root].iter().chain(args.iter()))
+    }
+
+    fn dead_reprs(report: &Report) -> Vec<String> {
+        report.dead_files.iter().map(|f| f.repr.clone()).collect()
+    }
+
+    #[test]
+    fn test_dead_files_sort_deterministically() {
+        let dead_file = DeadFile::for_test;
+        let expected = vec![
+            dead_file("A.py", "/project/A.py"),
+            dead_file("a.py", "/other/a.py"),
+            dead_file("a.py", "/project/a.py"),
+            dead_file("a/b.py", "/project/a/b.py"),
+            dead_file("a\\c.py", "/project/a/c.py"),
+            dead_file("B.py", "/project/B.py"),
+        ];
+        let mut reversed = expected.clone();
+        reversed.reverse();
+        reversed.sort();
+        assert_eq!(reversed, expected);
+        let mut rotated = expected.clone();
+        rotated.rotate_left(3);
+        rotated.sort();
+        assert_eq!(rotated, expected);
+    }
+
+    #[test]
+    fn test_missing_target_path() {
+        let dir = create_project(&[("main.py", "")]);
+        let missing = dir.path().join("typo");
+
+        let error = resolve_paths(vec![dir.path().to_path_buf(), missing.clone()]).unwrap_err();
+        assert!(error.to_string().contains(missing.to_str().unwrap()));
+        let cli = Cli::parse_from(["undead", missing.to_str().unwrap()]);
+        let error = find_dead_files(&cli).unwrap_err();
+        assert!(error.to_string().contains(missing.to_str().unwrap()));
+    }
+
+    #[test]
+    fn test_errors_are_typed() {
+        let dir = TempDir::new().unwrap();
+        let cli = cli_for(&dir, &["--root-detection", "markers"]);
+        assert!(matches!(
+            find_dead_files(&cli),
+            Err(UndeadError::NoPythonFiles)
+        ));
+
+        fs::write(dir.path().join("orphan.py"), "").unwrap();
+        assert!(matches!(
+            find_dead_files(&cli),
+            Err(UndeadError::RootNotFound)
+        ));
+
+        fs::write(dir.path().join("broken.py"), "def (:\n").unwrap();
+        let error = analyze_file(
+            &dir.path().join("broken.py"),
+            dir.path(),
+            &ImportOptions::default(),
+        )
+        .unwrap_err();
+        assert!(
+            matches!(error, UndeadError::ParseFailure { path, .. } if path.ends_with("broken.py"))
+        );
+    }
+
+    #[test]
+    fn test_relative_import_above_filesystem_root() {
+        let dir = create_project(&[("used.py", "")]);
+        let depth = dir.path().canonicalize().unwrap().ancestors().count();
+        let source = format!("from {} import x\nimport used\n", ".".repeat(depth + 2));
+        fs::write(dir.path().join("main.py"), &source).unwrap();
+
+        let report = find_dead_files(&cli_for(&dir, &[])).unwrap();
+        assert_eq!(dead_reprs(&report), vec!["main.py"]);
+        assert!(report.skipped.is_empty());
+        assert_eq!(report.warnings.len(), 1);
+        assert!(report.warnings[0].contains("goes above the filesystem root"));
+
+        // left to itself, resolving the import yields nothing rather than panicking
+        let body = match parse(&source, Mode::Module, "<embedded>") {
+            Ok(Mod::Module(ModModule { body, .. })) => body,
+            _ => panic!("failed to parse test source"),
+        };
+        let root = dir.path().canonicalize().unwrap();
+        let imports = match &body[0] {
+            Stmt::ImportFrom(import_from) => {
+                Import::from_import_from(import_from, &root.join("main.py"), &root, &root)
+            }
+            _ => panic!("expected an import from statement"),
+        };
+        assert!(imports.is_empty());
+    }
+
+    #[test]
+    fn test_huge_relative_import_level() {
+        let dir = create_project(&[("used.py", "")]);
+        let source = format!("from {}pkg import x\nimport used\n", ".".repeat(100_000));
+        fs::write(dir.path().join("main.py"), source).unwrap();
+
+        let report = find_dead_files(&cli_for(&dir, &[])).unwrap();
+        assert_eq!(dead_reprs(&report), vec!["main.py"]);
+        assert_eq!(report.warnings.len(), 1);
+        assert!(report.warnings[0].contains("`from <100000 dots>pkg import ...` goes above"));
+    }
+
+    #[test]
+    fn test_namespace_packages() {
+        let dir = create_project(&[
+            (
+                "main.py",
+                "import ns.first\nfrom ns import second\nfrom ns.sub import third\nimport ns.sub\n",
+            ),
+            ("ns/first.py", ""),
+            ("ns/second.py", ""),
+            ("ns/sub/third.py", ""),
+            ("ns/sub/unused.py", ""),
+        ]);
+        let root = dir.path().canonicalize().unwrap();
+        let imports =
+            extract_imports(&root.join("main.py"), &root, &ImportOptions::default()).unwrap();
+        assert_eq!(
+            imports,
+            vec![
+                Import::Module("ns.first".to_string()),
+                Import::Module("ns.second".to_string()),
+                Import::Module("ns.sub.third".to_string()),
+                Import::NamespacePackage("ns.sub".to_string()),
+            ]
+        );
+        assert_eq!(resolve_import(imports[3].clone()), "ns.sub");
+
+        let report = find_dead_files(&cli_for(&dir, &[])).unwrap();
+        assert_eq!(
+            dead_reprs(&report),
+            vec![
+                "main.py".to_string(),
+                format!("ns{MAIN_SEPARATOR_STR}sub{MAIN_SEPARATOR_STR}unused.py")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_from_import_from() {
+        let current_file_path = Path::new("/e/f/g/h.py");
+        let python_root = Path::new("/e/f");
+        match parse("from a.b import c, d", Mode::Module, "<embedded>") {
+            Ok(Mod::Module(ModModule {
+                range: _,
+                body,
+                type_ignores: _,
+            })) => {
+                let imports: Vec<Import> = body
+                    .iter()
+                    .flat_map(|stmt| match stmt {
+                        Stmt::Import(import) => Import::from_import(import, python_root, &[]),
+                        Stmt::ImportFrom(import_from) => Import::from_import_from(
+                            import_from,
+                            current_file_path,
+                            python_root,
+                            python_root,
+                        ),
+                        _ => vec![],
+                    })
+                    .collect();
+                assert_eq!(imports.len(), 2);
+                assert_eq!(imports[0], Import::Module("a.b.c".to_string()));
+                assert_eq!(imports[1], Import::Module("a.b.d".to_string()));
+            }
+            _ => panic!("failed to parse test source"),
+        };
+    }
+
+    #[test]
+    fn test_from_dot_import_sibling_module() {
+        let current_file_path = Path::new("/root/pkg/__init__.py");
+        let python_root = Path::new("/root");
+        match parse("from . import helpers", Mode::Module, "<embedded>") {
+            Ok(Mod::Module(ModModule {
+                range: _,
+                body,
+                type_ignores: _,
+            })) => {
+                let imports: Vec<Import> = body
+                    .iter()
+                    .flat_map(|stmt| match stmt {
+                        Stmt::ImportFrom(import_from) => Import::from_import_from(
+                            import_from,
+                            current_file_path,
+                            python_root,
+                            python_root,
+                        ),
+                        _ => vec![],
+                    })
+                    .collect();
+                assert_eq!(imports, vec![Import::Module("pkg.helpers".to_string())]);
+            }
+            _ => panic!("failed to parse test source"),
+        };
+    }
+
+    #[test]
+    fn test_star_imports_reach_submodules() {
+        let dir = create_project(&[
+            ("app/__init__.py", ""),
+            ("app/models/__init__.py", ""),
+            ("app/models/user.py", ""),
+            ("app/models/order.py", ""),
+            ("app/models/README.md", ""),
+            ("app/views/__init__.py", "__all__ = [\"home\"]\n"),
+            ("app/views/home.py", ""),
+            ("app/views/admin.py", ""),
+        ]);
+        let python_root = dir.path().canonicalize().unwrap();
+        let star_import = |source: &str| {
+            let body = match parse(source, Mode::Module, "<embedded>") {
+                Ok(Mod::Module(ModModule { body, .. })) => body,
+                _ => panic!("failed to parse test source"),
+            };
+            match &body[0] {
+                Stmt::ImportFrom(import_from) => Import::from_import_from(
+                    import_from,
+                    &python_root.join("app").join("__init__.py"),
+                    &python_root,
+                    &python_root,
+                ),
+                _ => panic!("expected an import from statement"),
+            }
+        };
+
+        assert_eq!(
+            star_import("from .models import *"),
+            vec![
+                Import::Package("app.models".to_string()),
+                Import::Module("app.models.order".to_string()),
+                Import::Module("app.models.user".to_string()),
+            ]
+        );
+        // `__all__` tells which submodules are exported
+        assert_eq!(
+            star_import("from .views import *"),
+            vec![
+                Import::Package("app.views".to_string()),
+                Import::Module("app.views.home".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_star_import_without_all_keeps_every_submodule_alive() {
+        let dir = create_project(&[
+            ("main.py", "from app.models import *\n"),
+            ("app/__init__.py", ""),
+            ("app/models/__init__.py", "from .user import User\n"),
+            ("app/models/user.py", ""),
+            ("app/models/order.py", ""),
+            ("app/models/legacy/__init__.py", ""),
+            ("app/models/legacy/invoice.py", ""),
+            ("app/views.py", ""),
+        ]);
+
+        let report = find_dead_files(&cli_for(&dir, &[])).unwrap();
+        // only direct submodules are exported, not those of subpackages
+        assert_eq!(
+            dead_reprs(&report),
+            vec![
+                format!("app{MAIN_SEPARATOR_STR}models{MAIN_SEPARATOR_STR}legacy{MAIN_SEPARATOR_STR}invoice.py"),
+                format!("app{MAIN_SEPARATOR_STR}views.py"),
+                "main.py".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_from_relative_dotted_module_import_from() {
+        let dir = create_project(&[
+            ("main.py", "import pkg.app\n"),
+            ("pkg/__init__.py", ""),
+            ("pkg/app.py", "from .subpkg.mod import name\n"),
+            ("pkg/subpkg/__init__.py", ""),
+            ("pkg/subpkg/mod.py", "name = 1\n"),
+            ("pkg/subpkg/other.py", ""),
+        ]);
+        let python_root = dir.path().canonicalize().unwrap();
+        let body = match parse("from .subpkg.mod import name", Mode::Module, "<embedded>") {
+            Ok(Mod::Module(ModModule { body, .. })) => body,
+            _ => panic!("failed to parse test source"),
+        };
+        let imports = match &body[0] {
+            Stmt::ImportFrom(import_from) => Import::from_import_from(
+                import_from,
+                &python_root.join("pkg").join("app.py"),
+                &python_root,
+                &python_root,
+            ),
+            _ => panic!("expected an import from statement"),
+        };
+        assert_eq!(imports, vec![Import::Module("pkg.subpkg.mod".to_string())]);
+
+        let report = find_dead_files(&cli_for(&dir, &[])).unwrap();
+        assert_eq!(
+            dead_reprs(&report),
+            vec![
+                "main.py".to_string(),
+                format!("pkg{MAIN_SEPARATOR_STR}subpkg{MAIN_SEPARATOR_STR}other.py")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_assume_used_modules_are_not_reported() {
+        let dir = create_project(&[
+            ("main.py", "if __name__ == '__main__':\n    pass\n"),
+            ("plugins/loaded.py", ""),
+            ("plugins/unused.py", ""),
+        ]);
+
+        let report = find_dead_files(&cli_for(&dir, &[])).unwrap();
+        assert!(dead_reprs(&report).contains(&format!("plugins{MAIN_SEPARATOR_STR}loaded.py")));
+
+        let report = find_dead_files(&cli_for(&dir, &["--assume-used", "plugins.loaded"])).unwrap();
+        assert_eq!(
+            dead_reprs(&report),
+            vec![format!("plugins{MAIN_SEPARATOR_STR}unused.py")]
+        );
+    }
+
+    #[test]
+    fn test_absolute_imports_resolve_against_their_own_root() {
+        let main_project = create_project(&[("main.py", "")]);
+        let other_project = create_project(&[
+            (
+                "app.py",
+                "from lib import helper\nif __name__ == '__main__':\n    helper()\n",
+            ),
+            ("lib.py", "def helper(): pass\n"),
+            ("unused.py", ""),
+        ]);
+
+        let cli = Cli::parse_from([
+            "undead",
+            main_project.path().to_str().unwrap(),
+            other_project.path().to_str().unwrap(),
+        ]);
+        let report = find_dead_files(&cli).unwrap();
+        let other_root = other_project.path().canonicalize().unwrap();
+        let dead_in_other_project: Vec<&str> = report
+            .dead_files
+            .iter()
+            .filter(|f| f.full_path.starts_with(&other_root))
+            .map(|f| f.repr.as_str())
+            .collect();
+        assert_eq!(dead_in_other_project, vec!["unused.py"]);
+    }
+
+    #[test]
+    fn test_streaming_matches_buffered_results() {
+        let dir = create_project(&[
+            (
+                "main.py",
+                "import pkg.used\nif __name__ == '__main__':\n    pass\n",
+            ),
+            ("pkg/__init__.py", ""),
+            ("pkg/used.py", ""),
+            ("pkg/unused.py", ""),
+            ("other.py", ""),
+        ]);
+        let cli = cli_for(&dir, &[]);
+
+        let mut streamed = vec![];
+        let summary = find_dead_files_streaming(&cli, |f| streamed.push(f)).unwrap();
+        streamed.sort();
+
+        let report = find_dead_files(&cli).unwrap();
+        assert_eq!(summary.scanned_files, report.scanned_files);
+        assert_eq!(streamed, report.dead_files);
+        assert_eq!(streamed.len(), 2);
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_strip_extended_length_prefix() {
+        assert_eq!(
+            strip_extended_length_prefix(PathBuf::from(r"\\?\C:\project\pkg\mod.py")),
+            PathBuf::from(r"C:\project\pkg\mod.py")
+        );
+        assert_eq!(
+            strip_extended_length_prefix(PathBuf::from(r"\\?\UNC\server\share\mod.py")),
+            PathBuf::from(r"\\server\share\mod.py")
+        );
+        assert_eq!(
+            strip_extended_length_prefix(PathBuf::from(r"C:\project\mod.py")),
+            PathBuf::from(r"C:\project\mod.py")
+        );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_render_as_import_string_with_extended_length_paths() {
+        let dir = create_project(&[("pkg/mod.py", "")]);
+        let root = resolve_paths(vec![dir.path().to_path_buf()])
+            .unwrap()
+            .remove(0);
+        assert!(!root.to_str().unwrap().starts_with(r"\\?\"));
+
+        let walked = parallel_build_path_iterator(
+            &[root.clone()],
+            &WalkFilters::default(),
+            &WalkMonitor::default(),
+        )
+        .unwrap();
+        let module = walked
+            .iter()
+            .find(|path| path.ends_with("mod.py"))
+            .map(|path| render_as_import_string(path, &root))
+            .unwrap();
+        assert_eq!(module, "pkg.mod");
+    }
+
+    #[test]
+    fn test_ignore_globs() {
+        let walk_filters = WalkFilters {
+            ignore_globs: build_ignore_globs(&[
+                "*.pyi".to_string(),
+                "**/migrations/**".to_string(),
+                "settings.py".to_string(),
+                "tests".to_string(),
+            ])
+            .unwrap(),
+            ignore_regex: None,
+        };
+        let is_ignored = |path: &str| walk_filters.is_ignored(Path::new(path));
+
+        assert!(is_ignored("/project/app/models.pyi"));
+        assert!(is_ignored("/project/app/migrations/0001_initial.py"));
+        assert!(is_ignored("/project/app/migrations/old/0000_legacy.py"));
+        assert!(is_ignored("/project/settings.py"));
+        assert!(is_ignored("/project/app/settings.py"));
+        assert!(is_ignored("/project/tests"));
+        assert!(!is_ignored("/project/app/models.py"));
+        assert!(!is_ignored("/project/app/migrations.py"));
+        assert!(!is_ignored("/project/app/local_settings.py"));
+        assert!(!is_ignored("/project/app/settings.py.bak"));
+
+        let dir = create_project(&[
+            ("main.py", ""),
+            ("app/stubs.pyi", ""),
+            ("app/migrations/0001_initial.py", ""),
+            ("tests/test_main.py", ""),
+        ]);
+        let report = find_dead_files(&cli_for(
+            &dir,
+            &[
+                "-I",
+                "**/migrations/**",
+                "-I",
+                "*.pyi",
+                "--ignore-globs",
+                "tests",
+            ],
+        ))
+        .unwrap();
+        assert_eq!(dead_reprs(&report), vec!["main.py"]);
+    }
+
+    #[test]
+    fn test_ignore_regex_excludes_matching_files() {
+        let dir = create_project(&[
+            ("api_generated.py", ""),
+            ("models/user_generated.py", ""),
+            ("models/user.py", ""),
+        ]);
+
+        let report = find_dead_files(&cli_for(&dir, &[])).unwrap();
+        assert_eq!(report.dead_files.len(), 3);
+
+        let report =
+            find_dead_files(&cli_for(&dir, &["--ignore-regex", r".*_generated\.py$"])).unwrap();
+        assert_eq!(
+            dead_reprs(&report),
+            vec![format!("models{MAIN_SEPARATOR_STR}user.py")]
+        );
+    }
+
+    #[test]
+    fn test_generated_pb2_pairs_are_dead_or_live_together() {
+        let dir = create_project(&[
+            ("main.py", "from protos import user_pb2\n"),
+            ("protos/__init__.py", ""),
+            ("protos/user_pb2.py", ""),
+            ("protos/user_pb2_grpc.py", "from protos import user_pb2\n"),
+            ("protos/order_pb2.py", ""),
+            ("protos/order_pb2_grpc.py", "from . import order_pb2\n"),
+        ]);
+
+        let report = find_dead_files(&cli_for(&dir, &[])).unwrap();
+        assert_eq!(
+            dead_reprs(&report),
+            vec![
+                "main.py".to_string(),
+                format!("protos{MAIN_SEPARATOR_STR}order_pb2.py"),
+                format!("protos{MAIN_SEPARATOR_STR}order_pb2_grpc.py"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_flat_modules_and_packages_resolve_together() {
+        let dir = create_project(&[
+            (
+                "main.py",
+                "from utils import helper\nfrom app.models import User\n\nif __name__ == '__main__':\n    helper(User)\n",
+            ),
+            ("utils.py", "def helper(x): pass\n"),
+            ("app/__init__.py", ""),
+            ("app/models.py", "class User: pass\n"),
+            ("app/views.py", ""),
+        ]);
+
+        let report = find_dead_files(&cli_for(&dir, &[])).unwrap();
+        assert_eq!(
+            dead_reprs(&report),
+            vec![format!("app{MAIN_SEPARATOR_STR}views.py")]
+        );
+    }
+
+    #[test]
+    fn test_init_files_are_import_sources() {
+        let dir = create_project(&[
+            (
+                "main.py",
+                "import pkg\nif __name__ == '__main__':\n    pass\n",
+            ),
+            ("pkg/__init__.py", "from .reexported import thing\n"),
+            ("pkg/reexported.py", "thing = 1\n"),
+            ("pkg/orphan.py", ""),
+        ]);
+
+        let report = find_dead_files(&cli_for(&dir, &[])).unwrap();
+        assert_eq!(
+            dead_reprs(&report),
+            vec![format!("pkg{MAIN_SEPARATOR_STR}orphan.py")]
+        );
+    }
+
+    #[test]
+    fn test_paths_from_file_scopes_candidates() {
+        let dir = create_project(&[
+            ("a.py", ""),
+            ("b.py", "import sub.c\n"),
+            ("sub/c.py", ""),
+            ("sub/d.py", ""),
+        ]);
+        let paths_file = dir.path().join("changed.txt");
+        fs::write(
+            &paths_file,
+            format!(
+                "{}\n\n{}\n",
+                dir.path().join("a.py").display(),
+                dir.path().join("sub").display()
+            ),
+        )
+        .unwrap();
+
+        let cli = Cli::parse_from(["undead", "--paths-from", paths_file.to_str().unwrap()]);
+        let report = find_dead_files(&cli).unwrap();
+        // b.py is dead too, but wasn't listed; it still keeps sub/c.py live
+        assert_eq!(
+            dead_reprs(&report),
+            vec!["a.py".to_string(), format!("sub{MAIN_SEPARATOR_STR}d.py")]
+        );
+    }
+
+    #[test]
+    fn test_warns_about_shadowed_stdlib_modules() {
+        let dir = create_project(&[
+            ("main.py", "import json\n"),
+            ("json.py", ""),
+            ("app/json.py", ""),
+        ]);
+
+        let report = find_dead_files(&cli_for(&dir, &[])).unwrap();
+        assert_eq!(report.warnings.len(), 1);
+        assert!(report.warnings[0].ends_with("json.py shadows the standard library module `json`"));
+    }
+
+    #[test]
+    fn test_relative_import_base_overrides_absolute_imports() {
+        let dir = create_project(&[
+            ("main.py", "from config import settings\n"),
+            ("config.py", "settings = {}\n"),
+            ("app/config.py", "settings = {}\n"),
+        ]);
+
+        let report = find_dead_files(&cli_for(&dir, &[])).unwrap();
+        assert!(!dead_reprs(&report).contains(&"config.py".to_string()));
+        assert!(dead_reprs(&report).contains(&format!("app{MAIN_SEPARATOR_STR}config.py")));
+
+        let app_dir = dir.path().join("app");
+        let report = find_dead_files(&cli_for(
+            &dir,
+            &["--relative-import-base", app_dir.to_str().unwrap()],
+        ))
+        .unwrap();
+        assert!(dead_reprs(&report).contains(&"config.py".to_string()));
+        assert!(!dead_reprs(&report).contains(&format!("app{MAIN_SEPARATOR_STR}config.py")));
+    }
+
+    #[test]
+    fn test_near_dead_files() {
+        let dir = create_project(&[
+            ("main.py", "import once\nimport twice\n"),
+            ("other.py", "import twice\n"),
+            ("once.py", ""),
+            ("twice.py", ""),
+        ]);
+
+        let report = find_dead_files(&cli_for(&dir, &[])).unwrap();
+        assert!(report.near_dead_files.is_empty());
+
+        let report = find_dead_files(&cli_for(&dir, &["--near-dead", "1"])).unwrap();
+        assert_eq!(dead_reprs(&report), vec!["main.py", "other.py"]);
+        assert_eq!(
+            report.near_dead_files,
+            vec![NearDeadFile {
+                file: DeadFile {
+                    repr: "once.py".to_string(),
+                    full_path: dir.path().canonicalize().unwrap().join("once.py"),
+                    confidence: Confidence::High,
+                    relative_path: relative_path(
+                        &dir.path().canonicalize().unwrap().join("once.py"),
+                        &std::env::current_dir().unwrap(),
+                    ),
+                },
+                importers: 1,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_root_detection_is_deterministic() {
+        let dir = create_project(&[
+            ("nested/pyproject.toml", ""),
+            ("nested/a.py", ""),
+            ("b.py", ""),
+            ("c/d.py", ""),
+        ]);
+        let root = dir.path().canonicalize().unwrap();
+        let targets = vec![root.join("c"), root.join("b.py"), root.join("nested")];
+
+        // `b.py` always comes first, `nested/a.py` would yield the nested project
+        let expected_root = root.clone();
+        for _ in 0..20 {
+            let walked_paths = parallel_build_path_iterator(
+                &targets,
+                &WalkFilters::default(),
+                &WalkMonitor::default(),
+            )
+            .unwrap();
+            assert!(walked_paths.windows(2).all(|pair| pair[0] <= pair[1]));
+            assert_eq!(
+                find_python_project_root(&walked_paths[0], ANY_MARKERS),
+                Some(expected_root.as_path())
+            );
+        }
+    }
+
+    #[test]
+    fn test_find_project_root_from_file() {
+        let dir = create_project(&[("pkg/mod.py", ""), ("nested/setup.py", "")]);
+        let root = dir.path().canonicalize().unwrap();
+
+        assert_eq!(
+            find_python_project_root(&root.join("pkg").join("mod.py"), ANY_MARKERS),
+            Some(root.as_path())
+        );
+        // a marker file is a file too, whose own directory is the root
+        let nested_marker = root.join("nested").join("setup.py");
+        assert_eq!(
+            find_python_project_root(&nested_marker, ANY_MARKERS),
+            Some(root.join("nested").as_path())
+        );
+    }
+
+    #[test]
+    fn test_find_entrypoints() {
+        let dir = create_project(&[
+            ("cli.py", "if __name__ == \"__main__\":\n    pass\n"),
+            ("lib.py", "import cli\n"),
+        ]);
+
+        let entrypoints = find_entrypoints(&cli_for(&dir, &["--list-entrypoints"])).unwrap();
+        assert_eq!(entrypoints.len(), 1);
+        assert_eq!(entrypoints[0].repr, "cli.py");
+        assert_eq!(entrypoints[0].reason.to_string(), "name-guard");
+    }
+
+    #[test]
+    fn test_report_duplicates() {
+        let dir = create_project(&[
+            ("main.py", "import app.utils\n"),
+            ("app/__init__.py", ""),
+            (
+                "app/utils.py",
+                "def slugify(text):\n    return text.lower()\n",
+            ),
+            ("legacy/__init__.py", ""),
+            (
+                "legacy/utils_copy.py",
+                "def slugify(text):\n    return text.lower()\n",
+            ),
+            ("legacy/other.py", "def other():\n    pass\n"),
+        ]);
+
+        let report = find_dead_files(&cli_for(&dir, &[])).unwrap();
+        assert!(report.duplicates.is_empty());
+        // blank package inits are alike without being copies
+        let report = find_dead_files(&cli_for(&dir, &["--report-duplicates"])).unwrap();
+        assert_eq!(
+            report.duplicates,
+            vec![vec![
+                format!("app{MAIN_SEPARATOR_STR}utils.py"),
+                format!("legacy{MAIN_SEPARATOR_STR}utils_copy.py"),
+            ]]
+        );
+    }
+
+    #[test]
+    fn test_single_job_gives_the_same_results() {
+        let dir = create_project(&[
+            ("main.py", "import app.core\nfrom app import helpers\n"),
+            ("app/__init__.py", ""),
+            ("app/core.py", "from . import models\n"),
+            ("app/models.py", ""),
+            ("app/helpers.py", ""),
+            ("app/legacy.py", "import app.old\n"),
+            ("app/old.py", ""),
+            ("scripts/run.py", "import app.unused\n"),
+            ("app/unused.py", ""),
+            ("orphan.py", ""),
+        ]);
+
+        let report = find_dead_files(&cli_for(&dir, &[])).unwrap();
+        let single_threaded = rayon::ThreadPoolBuilder::new()
+            .num_threads(1)
+            .build()
+            .unwrap()
+            .install(|| find_dead_files(&cli_for(&dir, &["--jobs", "1"])).unwrap());
+        assert_eq!(single_threaded.dead_files, report.dead_files);
+        assert_eq!(single_threaded.scanned_files, report.scanned_files);
+    }
+
+    #[test]
+    fn test_targets_are_picked_out_of_the_project_walk() {
+        let dir = create_project(&[
+            ("main.py", "import app.core\n"),
+            ("app/__init__.py", ""),
+            ("app/core.py", ""),
+            ("app/unused.py", ""),
+            ("app/tests/test_core.py", "import app.core\n"),
+        ]);
+        let root = dir.path().canonicalize().unwrap();
+
+        let report = find_dead_files(&cli_for(&dir, &["--ignore-globs", "tests"])).unwrap();
+        assert_eq!(
+            dead_reprs(&report),
+            vec![
+                format!("app{MAIN_SEPARATOR_STR}unused.py"),
+                "main.py".to_string()
+            ]
+        );
+        // the target subset is filtered out of the walk of the whole project
+        let cli = Cli::parse_from(["undead", root.join("app").to_str().unwrap()]);
+        let report = find_dead_files(&cli).unwrap();
+        assert_eq!(report.scanned_files, 5);
+        assert_eq!(
+            dead_reprs(&report),
+            vec![
+                format!("app{MAIN_SEPARATOR_STR}tests{MAIN_SEPARATOR_STR}test_core.py"),
+                format!("app{MAIN_SEPARATOR_STR}unused.py"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hidden_and_gitignored_targets_are_searched() {
+        let dir = create_project(&[
+            ("main.py", ""),
+            (".tools/lint.py", ""),
+            ("build/generated.py", ""),
+            (".gitignore", "build/\n"),
+        ]);
+        run_git(&dir, &["init", "-q"], None);
+        let root = dir.path().canonicalize().unwrap();
+
+        let report = find_dead_files(&cli_for(&dir, &[])).unwrap();
+        assert_eq!(dead_reprs(&report), vec!["main.py"]);
+        for (target, file) in [(".tools", "lint.py"), ("build", "generated.py")] {
+            let cli = Cli::parse_from(["undead", root.join(target).to_str().unwrap()]);
+            let report = find_dead_files(&cli).unwrap();
+            let dead_paths: Vec<&PathBuf> = report
+                .dead_files
+                .iter()
+                .map(|dead_file| &dead_file.full_path)
+                .collect();
+            assert_eq!(dead_paths, vec![&root.join(target).join(file)]);
+        }
+    }
+
+    /// Changes the contents of the file at `path` behind the cache's back, keeping its size and
+    /// modification time.
+    fn rewrite_unnoticed(path: &Path, contents: &str) {
+        let metadata = fs::metadata(path).unwrap();
+        assert_eq!(metadata.len() as usize, contents.len());
+        fs::write(path, contents).unwrap();
+        fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(metadata.modified().unwrap())
+            .unwrap();
+    }
+
+    #[test]
+    fn test_entrypoints_are_detected_from_the_source_read_for_imports() {
+        let source = "if __name__ == \"__main__\":\n    import lib\n";
+        let dir = create_project(&[
+            ("cli.py", source),
+            ("lib.py", "import helpers\n"),
+            ("helpers.py", ""),
+            ("unused.py", ""),
+        ]);
+        let cache_dir = tempfile::tempdir().unwrap();
+        let cli = cli_for(&dir, &["--cache-dir", cache_dir.path().to_str().unwrap()]);
+
+        let report = find_dead_files(&cli).unwrap();
+        assert_eq!(dead_reprs(&report), vec!["unused.py"]);
+        // cached analyses tell entrypoints apart too, without reading files again
+        let no_guard = format!("{:<width$}\n", "import os", width = source.len() - 1);
+        rewrite_unnoticed(&dir.path().join("cli.py"), &no_guard);
+        let report = find_dead_files(&cli).unwrap();
+        assert_eq!(dead_reprs(&report), vec!["unused.py"]);
+        let report = find_dead_files(&cli_for(&dir, &[])).unwrap();
+        assert_eq!(dead_reprs(&report), vec!["cli.py", "lib.py", "unused.py"]);
+    }
+
+    #[test]
+    fn test_find_candidates() {
+        let dir = create_project(&[
+            ("cli.py", "if __name__ == \"__main__\":\n    import lib\n"),
+            ("lib.py", ""),
+            ("unused.py", ""),
+        ]);
+
+        let candidates = find_candidates(&cli_for(&dir, &["--list-candidates"])).unwrap();
+        let reprs: Vec<&str> = candidates.iter().map(|file| file.repr.as_str()).collect();
+        assert_eq!(reprs, vec!["lib.py", "unused.py"]);
+    }
+
+    #[test]
+    fn test_from_package_import_submodule_and_symbol() {
+        let dir = create_project(&[
+            ("pkg/__init__.py", "def func(): pass\n"),
+            ("pkg/mod.py", ""),
+        ]);
+        let python_root = dir.path().canonicalize().unwrap();
+        let current_file_path = python_root.join("main.py");
+        let body = match parse("from pkg import mod, func", Mode::Module, "<embedded>") {
+            Ok(Mod::Module(ModModule { body, .. })) => body,
+            _ => panic!("failed to parse test source"),
+        };
+        let imports = match &body[0] {
+            Stmt::ImportFrom(import_from) => Import::from_import_from(
+                import_from,
+                &current_file_path,
+                &python_root,
+                &python_root,
+            ),
+            _ => panic!("expected an import from statement"),
+        };
+        assert_eq!(
+            imports,
+            vec![
+                Import::Module("pkg.mod".to_string()),
+                Import::Package("pkg".to_string())
+            ]
+        );
+        assert_eq!(
+            resolve_import(imports[1].clone()),
+            render_as_import_string(
+                &python_root.join("pkg").join(PYTHON_INIT_FILE),
+                &python_root
+            )
+        );
+    }
+
+    #[test]
+    fn test_cache_in_custom_directory() {
+        let dir = create_project(&[("main.py", "import used\n"), ("used.py", "")]);
+        let cache_dir = tempfile::tempdir().unwrap();
+        let cli = cli_for(&dir, &["--cache-dir", cache_dir.path().to_str().unwrap()]);
+
+        let uncached_report = find_dead_files(&cli).unwrap();
+        let cache_files: Vec<PathBuf> = fs::read_dir(cache_dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        assert_eq!(cache_files.len(), 1);
+        assert!(!dir.path().join(".undead_cache").exists());
+
+        let cached_report = find_dead_files(&cli).unwrap();
+        assert_eq!(cached_report.dead_files, uncached_report.dead_files);
+        assert_eq!(dead_reprs(&cached_report), vec!["main.py"]);
+    }
+
+    #[test]
+    fn test_cache_follows_the_all_of_star_imported_packages() {
+        let dir = create_project(&[
+            ("main.py", "from pkg import *\n"),
+            ("pkg/__init__.py", "__all__ = [\"x\"]\n"),
+            ("pkg/x.py", ""),
+            ("pkg/y.py", ""),
+        ]);
+        let cache_dir = tempfile::tempdir().unwrap();
+        let cli = cli_for(&dir, &["--cache-dir", cache_dir.path().to_str().unwrap()]);
+
+        let report = find_dead_files(&cli).unwrap();
+        assert_eq!(
+            dead_reprs(&report),
+            vec![
+                "main.py".to_string(),
+                format!("pkg{MAIN_SEPARATOR_STR}y.py")
+            ]
+        );
+        // only the package init changes, main.py's cached entry depends on it
+        fs::write(
+            dir.path().join("pkg/__init__.py"),
+            "__all__ = [\"y\"]  # moved\n",
+        )
+        .unwrap();
+        let report = find_dead_files(&cli).unwrap();
+        assert_eq!(
+            dead_reprs(&report),
+            vec![
+                "main.py".to_string(),
+                format!("pkg{MAIN_SEPARATOR_STR}x.py")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unparseable_files_are_skipped() {
+        let dir = create_project(&[("main.py", "def broken(:\n"), ("orphan.py", "")]);
+        let report = find_dead_files(&cli_for(&dir, &[])).unwrap();
+        assert_eq!(dead_reprs(&report), vec!["orphan.py"]);
+        assert_eq!(report.skipped.len(), 1);
+        assert_eq!(report.skipped[0].repr, "main.py");
+    }
+
+    #[test]
+    fn test_strict_fails_on_unparseable_files() {
+        let dir = create_project(&[("main.py", "def broken(:\n"), ("orphan.py", "")]);
+        let error = find_dead_files(&cli_for(&dir, &["--strict"])).unwrap_err();
+        assert!(matches!(
+            error,
+            UndeadError::ParseFailure { path, message }
+                if path.ends_with("main.py") && message.starts_with("1 files failed to parse")
+        ));
+    }
+
+    #[test]
+    fn test_find_import_roots() {
+        let dir = create_project(&[
+            ("app/pyproject.toml", ""),
+            ("app/main.py", ""),
+            ("app/base/__init__.py", ""),
+            ("app/src/pkg/__init__.py", ""),
+            ("tool/setup.py", ""),
+        ]);
+        let app = dir.path().canonicalize().unwrap().join("app");
+        let tool = dir.path().canonicalize().unwrap().join("tool");
+        let cli = Cli::parse_from([
+            "undead",
+            app.to_str().unwrap(),
+            tool.to_str().unwrap(),
+            "--relative-import-base",
+            app.join("base").to_str().unwrap(),
+            "--namespace-root",
+            app.join("src").to_str().unwrap(),
+        ]);
+
+        let roots: Vec<(PathBuf, ImportRootKind)> = find_import_roots(&cli)
+            .unwrap()
+            .into_iter()
+            .map(|root| (root.path, root.kind))
+            .collect();
+        assert_eq!(
+            roots,
+            vec![
+                (app.join("base"), ImportRootKind::RelativeImportBase),
+                (app.clone(), ImportRootKind::ProjectRoot),
+                (tool, ImportRootKind::TargetRoot),
+                (app.join("src"), ImportRootKind::NamespaceRoot),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_group_targets_by_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = dir.path().canonicalize().unwrap();
+        let mut targets = Vec::new();
+        for project in ["alpha", "beta", "gamma"] {
+            fs::create_dir_all(base.join(project)).unwrap();
+            fs::write(base.join(project).join("pyproject.toml"), "").unwrap();
+        }
+        for index in 0..30 {
+            let project = ["gamma", "alpha", "beta"][index % 3];
+            let target = base.join(project).join(format!("pkg{index}"));
+            fs::create_dir_all(&target).unwrap();
+            targets.push(target);
+        }
+        fs::create_dir_all(base.join("loose")).unwrap();
+        targets.push(base.join("loose"));
+
+        let groups = group_targets_by_root(&targets, Some(RootDetection::Markers));
+        let roots: Vec<&PathBuf> = groups.iter().map(|(root, _)| root).collect();
+        assert_eq!(
+            roots,
+            vec![&base.join("gamma"), &base.join("alpha"), &base.join("beta")]
+        );
+        for (root, group) in groups.iter() {
+            assert_eq!(group.len(), 10);
+            assert!(group.iter().all(|target| target.starts_with(root)));
+        }
+    }
+
+    #[test]
+    fn test_strict_fails_on_unresolvable_imports() {
+        let dir = create_project(&[
+            (
+                "main.py",
+                "import importlib\n\nimportlib.import_module(\"plugin\")\n",
+            ),
+            ("plugin.py", ""),
+        ]);
+        assert!(find_dead_files(&cli_for(&dir, &["--strict"])).is_ok());
+
+        let dir = create_project(&[
+            (
+                "main.py",
+                "import importlib\n\nname = \"plugin\"\nimportlib.import_module(name)\n",
+            ),
+            ("plugin.py", ""),
+        ]);
+        assert!(find_dead_files(&cli_for(&dir, &[])).is_ok());
+        let error = find_dead_files(&cli_for(&dir, &["--strict"])).unwrap_err();
+        assert!(matches!(
+            error,
+            UndeadError::Config(message)
+                if message.contains("`import_module` called with a non-literal module in ")
+                    && message.contains("main.py")
+        ));
+    }
+
+    #[test]
+    fn test_sort_by_lines() {
+        let dir = create_project(&[
+            ("a_small.py", "x = 1\n"),
+            ("b_large.py", "x = 1\ny = 2\nz = 3\n"),
+            ("c_medium.py", "x = 1\ny = 2\n"),
+            ("d_small.py", "x = 1\n"),
+        ]);
+        let report = find_dead_files(&cli_for(&dir, &[])).unwrap();
+        assert_eq!(
+            dead_reprs(&report),
+            vec!["a_small.py", "b_large.py", "c_medium.py", "d_small.py"]
+        );
+        let report = find_dead_files(&cli_for(&dir, &["--sort", "lines"])).unwrap();
+        assert_eq!(
+            dead_reprs(&report),
+            vec!["b_large.py", "c_medium.py", "a_small.py", "d_small.py"]
+        );
+    }
+
+    #[test]
+    fn test_throwaway_alias_imports_keep_modules_alive() {
+        let dir = create_project(&[
+            (
+                "main.py",
+                "from pkg import plugin as _\nimport pkg.other as _\n",
+            ),
+            ("pkg/__init__.py", "from . import registered as _\n"),
+            ("pkg/plugin.py", ""),
+            ("pkg/other.py", ""),
+            ("pkg/registered.py", ""),
+            ("pkg/unused.py", ""),
+        ]);
+        let report = find_dead_files(&cli_for(&dir, &[])).unwrap();
+        assert_eq!(
+            dead_reprs(&report),
+            vec![
+                "main.py",
+                format!("pkg{MAIN_SEPARATOR_STR}unused.py").as_str()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_namespace_roots_share_imports() {
+        let dir = create_project(&[
+            (
+                "app/main.py",
+                "import shared.util\nfrom common import helpers\n",
+            ),
+            ("app/unused.py", ""),
+            ("libs/shared/__init__.py", ""),
+            ("libs/shared/util.py", ""),
+            ("libs/common/helpers.py", ""),
+            ("libs/common/orphan.py", ""),
+        ]);
+        let report = find_dead_files(&cli_for(
+            &dir,
+            &[
+                "--namespace-root",
+                dir.path().join("app").to_str().unwrap(),
+                "--namespace-root",
+                dir.path().join("libs").to_str().unwrap(),
+            ],
+        ))
+        .unwrap();
+        // files are named after the namespace root they live in
+        assert_eq!(
+            dead_reprs(&report),
+            vec!["common/orphan.py", "main.py", "unused.py"]
+        );
+    }
+
+    #[test]
+    fn test_src_layout_imports_through_either_root() {
+        let dir = create_project(&[
+            ("main.py", "import pkg.core\n"),
+            ("src/pkg/__init__.py", ""),
+            ("src/pkg/core.py", ""),
+            ("src/pkg/util.py", ""),
+            ("src/pkg/orphan.py", ""),
+            (
+                "tests/test_util.py",
+                "import src.pkg.util\nfrom src.pkg import core\n",
+            ),
+        ]);
+        let report = find_dead_files(&cli_for(
+            &dir,
+            &["--namespace-root", dir.path().join("src").to_str().unwrap()],
+        ))
+        .unwrap();
+        assert_eq!(
+            dead_reprs(&report),
+            vec![
+                "main.py".to_string(),
+                format!("pkg{MAIN_SEPARATOR_STR}orphan.py"),
+                format!("tests{MAIN_SEPARATOR_STR}test_util.py"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_deadness_index() {
+        let dir = create_project(&[
+            (
+                "main.py",
+                "import used\n\nif __name__ == \"__main__\":\n    pass\n",
+            ),
+            ("used.py", ""),
+            ("unused.py", ""),
+            ("pkg/__init__.py", ""),
+        ]);
+        let index = DeadnessIndex::build(&cli_for(&dir, &[])).unwrap();
+        assert_eq!(index.is_dead(&dir.path().join("unused.py")), Some(true));
+        assert_eq!(index.is_dead(&dir.path().join("used.py")), Some(false));
+        // entrypoints, package inits and unknown files aren't candidates
+        assert_eq!(index.is_dead(&dir.path().join("main.py")), None);
+        assert_eq!(index.is_dead(&dir.path().join("pkg/__init__.py")), None);
+        assert_eq!(index.is_dead(&dir.path().join("missing.py")), None);
+    }
+
+    #[test]
+    fn test_import_multiple_names_resolve_independently() {
+        let dir = create_project(&[
+            ("main.py", "import pkg, mod\n"),
+            ("pkg/__init__.py", ""),
+            ("mod.py", ""),
+        ]);
+        let root = dir.path().canonicalize().unwrap();
+        let imports: Vec<String> =
+            extract_imports(&root.join("main.py"), &root, &ImportOptions::default())
+                .unwrap()
+                .into_iter()
+                .map(resolve_import)
+                .collect();
+        assert_eq!(imports, vec!["pkg.__init__", "mod"]);
+
+        let report = find_dead_files(&cli_for(&dir, &[])).unwrap();
+        assert_eq!(dead_reprs(&report), vec!["main.py"]);
+    }
+
+    fn run_git(dir: &TempDir, args: &[&str], date: Option<&str>) {
+        let mut command = std::process::Command::new("git");
+        command
+            .current_dir(dir.path())
+            .args([
+                "-c",
+                "user.name=undead",
+                "-c",
+                "user.email=undead@example.com",
+            ])
+            .args(args);
+        if let Some(date) = date {
+            command
+                .env("GIT_AUTHOR_DATE", date)
+                .env("GIT_COMMITTER_DATE", date);
+        }
+        assert!(command.output().unwrap().status.success());
+    }
+
+    #[test]
+    fn test_min_age_skips_recently_committed_files() {
+        let dir = create_project(&[
+            ("old.py", ""),
+            ("pkg/old.py", ""),
+            ("new.py", ""),
+            ("untracked.py", ""),
+        ]);
+        let git = |args: &[&str], date: Option<&str>| run_git(&dir, args, date);
+        git(&["init", "-q"], None);
+        git(&["add", "old.py", "pkg/old.py", "new.py"], None);
+        git(&["commit", "-q", "-m", "old"], Some("2000-01-01T00:00:00"));
+        std::fs::write(dir.path().join("new.py"), "x = 1\n").unwrap();
+        git(&["add", "new.py"], None);
+        git(&["commit", "-q", "-m", "new"], None);
+
+        let report = find_dead_files(&cli_for(&dir, &["--min-age", "30"])).unwrap();
+        assert_eq!(
+            dead_reprs(&report),
+            vec!["old.py", "pkg/old.py", "untracked.py"]
+        );
+        let report = find_dead_files(&cli_for(&dir, &[])).unwrap();
+        assert_eq!(
+            dead_reprs(&report),
+            vec!["new.py", "old.py", "pkg/old.py", "untracked.py"]
+        );
+    }
+
+    #[test]
+    fn test_submodule_imports_credit_ancestor_inits() {
+        let dir = create_project(&[
+            ("main.py", "import pkg.sub.leaf\n"),
+            ("pkg/__init__.py", ""),
+            ("pkg/sub/__init__.py", ""),
+            ("pkg/sub/leaf.py", ""),
+            ("other/__init__.py", ""),
+        ]);
+        let report = find_dead_files(&cli_for(&dir, &["--include-init"])).unwrap();
+        assert_eq!(dead_reprs(&report), vec!["main.py", "other/__init__.py"]);
+        // package inits stay out of the report by default
+        let report = find_dead_files(&cli_for(&dir, &[])).unwrap();
+        assert_eq!(dead_reprs(&report), vec!["main.py"]);
+    }
+
+    #[test]
+    fn test_quiet_errors_only_counts_walk_monitor() {
+        let walk_monitor = WalkMonitor {
+            quiet: true,
+            ..Default::default()
+        };
+        assert_eq!(walk_monitor.summary(), None);
+        walk_monitor.report(ignore::Error::from(std::io::Error::other("first")));
+        walk_monitor.report(ignore::Error::from(std::io::Error::other("second")));
+        // walking the project again doesn't count the same errors twice
+        walk_monitor.report(ignore::Error::from(std::io::Error::other("first")));
+        assert_eq!(
+            walk_monitor.summary(),
+            Some("2 paths couldn't be walked (hidden by --quiet-errors)".to_string())
+        );
+        let walk_monitor = WalkMonitor::default();
+        walk_monitor.report(ignore::Error::from(std::io::Error::other("first")));
+        assert_eq!(walk_monitor.summary(), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_quiet_errors_summarizes_unreadable_directories() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = create_project(&[("main.py", ""), ("locked/hidden.py", "")]);
+        let locked = dir.path().join("locked");
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o000)).unwrap();
+        // permissions can't be simulated when running as root
+        let unreadable = fs::read_dir(&locked).is_err();
+        let report = find_dead_files(&cli_for(&dir, &["--quiet-errors"]));
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+        if unreadable {
+            let report = report.unwrap();
+            assert_eq!(dead_reprs(&report), vec!["main.py"]);
+            assert_eq!(
+                report.warnings,
+                vec!["1 path couldn't be walked (hidden by --quiet-errors)"]
+            );
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_walk_survives_symlink_loops() {
+        let dir = create_project(&[("main.py", ""), ("pkg/mod.py", "")]);
+        std::os::unix::fs::symlink(dir.path(), dir.path().join("pkg").join("loop")).unwrap();
+        std::os::unix::fs::symlink("..", dir.path().join("pkg").join("parent")).unwrap();
+        let report = find_dead_files(&cli_for(&dir, &["--max-walk-time", "60"])).unwrap();
+        assert_eq!(
+            dead_reprs(&report),
+            vec![
+                "main.py".to_string(),
+                format!("pkg{MAIN_SEPARATOR_STR}mod.py")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_max_walk_time_aborts_walks() {
+        let dir = create_project(&[("main.py", "")]);
+        let error = find_dead_files(&cli_for(&dir, &["--max-walk-time", "0"])).unwrap_err();
+        assert!(error.to_string().contains("took longer than 0s"));
+    }
+
+    #[test]
+    fn test_empty_init_flags() {
+        let dir = create_project(&[
+            ("main.py", ""),
+            ("empty/__init__.py", "# nothing to see here\n\n"),
+            ("full/__init__.py", "VERSION = 1\n"),
+        ]);
+        let reprs = |args: &[&str]| dead_reprs(&find_dead_files(&cli_for(&dir, args)).unwrap());
+        let empty_init = format!("empty{MAIN_SEPARATOR_STR}__init__.py");
+        let full_init = format!("full{MAIN_SEPARATOR_STR}__init__.py");
+
+        assert_eq!(reprs(&[]), vec!["main.py"]);
+        assert_eq!(reprs(&["--ignore-empty-init"]), vec!["main.py"]);
+        assert_eq!(
+            reprs(&["--report-empty-init"]),
+            vec![empty_init.as_str(), "main.py"]
+        );
+        assert_eq!(
+            reprs(&["--include-init"]),
+            vec![empty_init.as_str(), full_init.as_str(), "main.py"]
+        );
+        assert_eq!(
+            reprs(&["--include-init", "--ignore-empty-init"]),
+            vec![full_init.as_str(), "main.py"]
+        );
+        assert_eq!(
+            reprs(&["--include-init", "--report-empty-init"]),
+            vec![empty_init.as_str(), full_init.as_str(), "main.py"]
+        );
+        assert!(Cli::try_parse_from(
+            ["undead", "--ignore-empty-init", "--report-empty-init", ".",]
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_relative_path() {
+        let base = Path::new("/home/user/project");
+        assert_eq!(
+            relative_path(Path::new("/home/user/project/pkg/mod.py"), base),
+            Path::new("pkg/mod.py")
+        );
+        assert_eq!(
+            relative_path(Path::new("/home/user/other/mod.py"), base),
+            Path::new("../other/mod.py")
+        );
+    }
+
+    #[test]
+    fn test_dead_file_json_representations() {
+        let dir = create_project(&[("pkg/__init__.py", ""), ("pkg/dead.py", "")]);
+        let report = find_dead_files(&cli_for(&dir, &[])).unwrap();
+        let json = serde_json::to_value(&report.dead_files[0]).unwrap();
+        let absolute_path = dir
+            .path()
+            .canonicalize()
+            .unwrap()
+            .join("pkg")
+            .join("dead.py");
+        assert_eq!(json["module"], "pkg.dead");
+        assert_eq!(json["full_path"], absolute_path.to_str().unwrap());
+        assert!(json.get("absolute_path").is_none());
+        let relative_path = PathBuf::from(json["relative_path"].as_str().unwrap());
+        assert!(relative_path.is_relative());
+        assert_eq!(
+            std::env::current_dir()
+                .unwrap()
+                .join(relative_path)
+                .canonicalize()
+                .unwrap(),
+            absolute_path
+        );
+        // the extra fields don't get in the way of reading reports back
+        let dead_file: DeadFile = serde_json::from_value(json).unwrap();
+        assert_eq!(dead_file, report.dead_files[0]);
+    }
+
+    #[test]
+    fn test_dead_file_module() {
+        let dead_file =
+            |repr: &str| DeadFile::for_test(&repr.replace('/', MAIN_SEPARATOR_STR), PathBuf::new());
+        assert_eq!(
+            dead_file("app/utils/helpers.py").module(),
+            "app.utils.helpers"
+        );
+        assert_eq!(dead_file("main.py").module(), "main");
+        assert_eq!(dead_file("app/__init__.py").module(), "app");
+    }
+
+    #[test]
+    fn test_tree_stats_aggregates_by_directory() {
+        let dir = create_project(&[
+            ("main.py", "import api.used\n"),
+            ("api/__init__.py", ""),
+            ("api/used.py", ""),
+            ("api/dead.py", ""),
+            ("jobs/__init__.py", ""),
+            ("jobs/first.py", ""),
+            ("jobs/second.py", ""),
+        ]);
+        let stats = find_tree_stats(&cli_for(&dir, &[])).unwrap();
+        let stats: Vec<(&str, usize, usize)> = stats
+            .iter()
+            .map(|stats| (stats.repr.as_str(), stats.dead_files, stats.total_files))
+            .collect();
+        assert_eq!(stats, vec![("jobs", 2, 2), (".", 1, 1), ("api", 1, 2)]);
+    }
+
+    #[test]
+    fn test_explain() {
+        let dir = create_project(&[
+            ("main.py", "import pkg.used\n"),
+            ("other.py", "from pkg import used\n"),
+            ("pkg/__init__.py", ""),
+            ("pkg/used.py", ""),
+            ("pkg/dead.py", ""),
+        ]);
+        let cli = cli_for(&dir, &[]);
+
+        let explanation = explain(&cli, "pkg.used").unwrap();
+        assert_eq!(explanation.module, "pkg.used");
+        assert!(explanation.is_used);
+        assert_eq!(explanation.importers, vec!["main.py", "other.py"]);
+
+        let dead_path = dir.path().join("pkg").join("dead.py");
+        let explanation = explain(&cli, dead_path.to_str().unwrap()).unwrap();
+        assert_eq!(explanation.module, "pkg.dead");
+        assert_eq!(
+            explanation.full_path,
+            Some(dead_path.canonicalize().unwrap())
+        );
+        assert!(!explanation.is_used);
+        assert!(explanation.importers.is_empty());
+
+        let explanation = explain(&cli, "pkg").unwrap();
+        assert_eq!(explanation.module, "pkg.__init__");
+        assert!(explanation.is_used);
+
+        let explanation = explain(&cli, "missing").unwrap();
+        assert_eq!(explanation.full_path, None);
+        assert!(!explanation.is_used);
+    }
+
+    #[test]
+    fn test_baselines_are_merged() {
+        let dir = create_project(&[("first.py", ""), ("second.py", ""), ("third.py", "")]);
+        let report = find_dead_files(&cli_for(&dir, &[])).unwrap();
+        for (name, kept) in [("first.json", "first.py"), ("second.json", "second.py")] {
+            let baseline = Report::new(
+                report
+                    .dead_files
+                    .iter()
+                    .filter(|dead_file| dead_file.repr == kept)
+                    .cloned()
+                    .collect(),
+                Summary::default(),
+            );
+            fs::write(
+                dir.path().join(name),
+                serde_json::to_string(&baseline).unwrap(),
+            )
+            .unwrap();
+        }
+        let first = dir.path().join("first.json");
+        let second = dir.path().join("second.json");
+
+        let report =
+            find_dead_files(&cli_for(&dir, &["--baseline", first.to_str().unwrap()])).unwrap();
+        assert_eq!(dead_reprs(&report), vec!["second.py", "third.py"]);
+        let report = find_dead_files(&cli_for(
+            &dir,
+            &[
+                "--baseline",
+                first.to_str().unwrap(),
+                "--baseline",
+                second.to_str().unwrap(),
+            ],
+        ))
+        .unwrap();
+        assert_eq!(dead_reprs(&report), vec!["third.py"]);
+    }
+
+    #[test]
+    fn test_baseline_lists_suppress_known_dead_files() {
+        let dir = create_project(&[("first.py", ""), ("pkg/second.py", ""), ("third.py", "")]);
+        let baseline = dir.path().join("baseline.txt");
+        fs::write(
+            &baseline,
+            "# known dead
+first.py
+pkg.second
+",
+        )
+        .unwrap();
+
+        let report =
+            find_dead_files(&cli_for(&dir, &["--baseline", baseline.to_str().unwrap()])).unwrap();
+        assert_eq!(dead_reprs(&report), vec!["third.py"]);
+    }
+
+    #[test]
+    fn test_visit_stmt_assign_records_all() {
+        let python_root = Path::new("/root");
+        let body = match parse("__all__ = [\"a\", \"b\"]", Mode::Module, "<embedded>") {
+            Ok(Mod::Module(ModModule { body, .. })) => body,
+            _ => panic!("failed to parse test source"),
+        };
+        let mut visitor = ImportVisitor::new(
+            Path::new("/root/pkg/__init__.py"),
+            python_root,
+            &ImportOptions::default(),
+        );
+        body.into_iter().for_each(|stmt| visitor.visit_stmt(stmt));
+        assert_eq!(
+            visitor.imports,
+            vec![
+                Import::Module("pkg.a".to_string()),
+                Import::Module("pkg.b".to_string())
+            ]
+        );
+        assert!(!visitor.dynamic_all);
+    }
+
+    #[test]
+    fn test_all_exports_submodules() {
+        let dir = create_project(&[
+            ("main.py", "import pkg\n"),
+            (
+                "pkg/__init__.py",
+                "__all__ = [\"exported\", \"CONSTANT\"]\n",
+            ),
+            ("pkg/exported.py", ""),
+            ("pkg/unexported.py", ""),
+        ]);
+        let report = find_dead_files(&cli_for(&dir, &[])).unwrap();
+        assert_eq!(
+            dead_reprs(&report),
+            vec!["main.py", &format!("pkg{MAIN_SEPARATOR_STR}unexported.py")]
+        );
+        assert!(report.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_dynamic_all_considers_all_submodules_used() {
+        let dir = create_project(&[
+            ("main.py", "import pkg\n"),
+            (
+                "pkg/__init__.py",
+                "from .base import base\n\n__all__ = base + [\"extra\"]\n",
+            ),
+            ("pkg/base.py", "base = []\n"),
+            ("pkg/extra.py", ""),
+            ("pkg/other.py", ""),
+            ("pkg/sub/__init__.py", ""),
+        ]);
+        let report = find_dead_files(&cli_for(&dir, &["--include-init"])).unwrap();
+        assert_eq!(dead_reprs(&report), vec!["main.py"]);
+        assert_eq!(report.warnings.len(), 1);
+        assert!(report.warnings[0].contains("builds `__all__` dynamically"));
+    }
+
+    #[test]
+    fn test_importlib_resources_keep_packages_used() {
+        let dir = create_project(&[
+            (
+                "main.py",
+                "import importlib.resources\nfrom importlib.resources import read_text as read\n\n\
+                 importlib.resources.files(\"templates\")\nread(\"data.schemas\", \"user.json\")\n",
+            ),
+            ("templates/__init__.py", ""),
+            ("data/__init__.py", ""),
+            ("data/schemas/__init__.py", ""),
+            ("unused/__init__.py", ""),
+        ]);
+        let report = find_dead_files(&cli_for(&dir, &["--include-init"])).unwrap();
+        assert_eq!(
+            dead_reprs(&report),
+            vec![
+                "main.py".to_string(),
+                format!("unused{MAIN_SEPARATOR_STR}__init__.py")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dynamic_imports_lower_confidence() {
+        let dir = create_project(&[("main.py", "import used\n"), ("used.py", "")]);
+        let report = find_dead_files(&cli_for(&dir, &[])).unwrap();
+        assert_eq!(report.dead_files[0].confidence, Confidence::High);
+
+        let dir = create_project(&[
+            ("main.py", "import used\n\ngetattr(used, \"name\")\n"),
+            ("used.py", ""),
+        ]);
+        let report = find_dead_files(&cli_for(&dir, &[])).unwrap();
+        assert_eq!(report.dead_files[0].confidence, Confidence::Medium);
+
+        let dir = create_project(&[
+            (
+                "main.py",
+                "import importlib\n\nimportlib.import_module(\"plugin\")\n",
+            ),
+            ("plugin.py", ""),
+        ]);
+        let report = find_dead_files(&cli_for(&dir, &[])).unwrap();
+        assert_eq!(dead_reprs(&report), vec!["main.py", "plugin.py"]);
+        assert!(report
+            .dead_files
+            .iter()
+            .all(|dead_file| dead_file.confidence == Confidence::Low));
+        let report = find_dead_files(&cli_for(&dir, &["--min-confidence", "medium"])).unwrap();
+        assert!(report.dead_files.is_empty());
+    }
+
+    #[test]
+    fn test_explicit_init_imports() {
+        let dir = create_project(&[
+            (
+                "main.py",
+                "from pkg.__init__ import x\nimport other.__init__\n",
+            ),
+            ("pkg/__init__.py", "x = 1\n"),
+            ("other/__init__.py", ""),
+            ("unused/__init__.py", ""),
+        ]);
+        let root = dir.path().canonicalize().unwrap();
+        let imports: Vec<String> =
+            extract_imports(&root.join("main.py"), &root, &ImportOptions::default())
+                .unwrap()
+                .into_iter()
+                .map(resolve_import)
+                .collect();
+        assert_eq!(imports, vec!["pkg.__init__", "other.__init__"]);
+
+        let report = find_dead_files(&cli_for(&dir, &["--include-init"])).unwrap();
+        assert_eq!(dead_reprs(&report), vec!["main.py", "unused/__init__.py"]);
+    }
+
+    #[test]
+    fn test_scripts_dirs_are_entrypoints() {
+        let dir = create_project(&[
+            ("scripts/migrate.py", ""),
+            ("bin/tool.py", ""),
+            ("tools/cleanup.py", ""),
+            ("orphan.py", ""),
+        ]);
+        let tools = dir.path().join("tools");
+        let report = find_dead_files(&cli_for(
+            &dir,
+            &[
+                "--treat-scripts-dir-as-entrypoints",
+                tools.to_str().unwrap(),
+            ],
+        ))
+        .unwrap();
+        assert_eq!(dead_reprs(&report), vec!["orphan.py"]);
+
+        let report = find_dead_files(&cli_for(&dir, &["--no-default-scripts-dirs"])).unwrap();
+        assert_eq!(
+            dead_reprs(&report),
+            vec![
+                "bin/tool.py",
+                "orphan.py",
+                "scripts/migrate.py",
+                "tools/cleanup.py"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_main_modules_are_entrypoints() {
+        let dir = create_project(&[
+            ("cli/__init__.py", ""),
+            ("cli/__main__.py", "from cli import run\n\nrun()\n"),
+            ("orphan.py", ""),
+        ]);
+        let report = find_dead_files(&cli_for(&dir, &[])).unwrap();
+        assert_eq!(dead_reprs(&report), vec!["orphan.py"]);
+        let entrypoints = find_entrypoints(&cli_for(&dir, &["--list-entrypoints"])).unwrap();
+        assert_eq!(entrypoints.len(), 1);
+        assert_eq!(entrypoints[0].reason.to_string(), "main-module");
+    }
+
+    #[test]
+    fn test_asyncio_run_entrypoints() {
+        let dir = create_project(&[
+            (
+                "serve.py",
+                "import asyncio\n\nasync def main():\n    pass\n\nasyncio.run(main())\n",
+            ),
+            (
+                "helper.py",
+                "import asyncio\n\ndef run():\n    asyncio.run(work())\n",
+            ),
+        ]);
+        let report = find_dead_files(&cli_for(&dir, &[])).unwrap();
+        assert_eq!(dead_reprs(&report), vec!["helper.py", "serve.py"]);
+        let report = find_dead_files(&cli_for(&dir, &["--asyncio-run-entrypoints"])).unwrap();
+        assert_eq!(dead_reprs(&report), vec!["helper.py"]);
+    }
+
+    #[test]
+    fn test_shebang_scripts_are_entrypoints() {
+        let dir = create_project(&[
+            ("tool.py", "#!/usr/bin/env python3\nprint(\"hello\")\n"),
+            ("shell.py", "#!/bin/sh\n"),
+            ("orphan.py", "# not a shebang: #!/usr/bin/env python\n"),
+        ]);
+        let report = find_dead_files(&cli_for(&dir, &[])).unwrap();
+        assert_eq!(dead_reprs(&report), vec!["orphan.py", "shell.py"]);
+        let entrypoints = find_entrypoints(&cli_for(&dir, &["--list-entrypoints"])).unwrap();
+        assert_eq!(entrypoints[0].reason.to_string(), "shebang");
+
+        let report = find_dead_files(&cli_for(&dir, &["--no-shebang-entrypoints"])).unwrap();
+        assert_eq!(
+            dead_reprs(&report),
+            vec!["orphan.py", "shell.py", "tool.py"]
+        );
+    }
+
+    #[test]
+    fn test_ignore_type_checking_imports() {
+        let dir = create_project(&[
+            (
+                "main.py",
+                "import typing\nfrom typing import TYPE_CHECKING\n\n\
+                 if TYPE_CHECKING:\n    from hints import Hint\n\
+                 if typing.TYPE_CHECKING:\n    import more_hints\nelse:\n    import runtime\n",
+            ),
+            ("hints.py", ""),
+            ("more_hints.py", ""),
+            ("runtime.py", ""),
+        ]);
+        let report = find_dead_files(&cli_for(&dir, &[])).unwrap();
+        assert_eq!(dead_reprs(&report), vec!["main.py"]);
+        let report = find_dead_files(&cli_for(&dir, &["--ignore-type-checking-imports"])).unwrap();
+        assert_eq!(
+            dead_reprs(&report),
+            vec!["hints.py", "main.py", "more_hints.py"]
+        );
+    }
+
+    #[test]
+    fn test_interactive_parse_mode() {
+        let dir = create_project(&[
+            ("cell.py", "import used\nawait used.run()\n"),
+            ("used.py", ""),
+        ]);
+        let root = dir.path().canonicalize().unwrap();
+        let options = ImportOptions {
+            parse_mode: ParseMode::Interactive,
+            ..ImportOptions::default()
+        };
+        let imports = extract_imports(&root.join("cell.py"), &root, &options).unwrap();
+        assert_eq!(imports, vec![Import::Module("used".to_string())]);
+
+        let report = find_dead_files(&cli_for(&dir, &["--parse-mode", "interactive"])).unwrap();
+        assert_eq!(dead_reprs(&report), vec!["cell.py"]);
+        assert!(report.skipped.is_empty());
+    }
+
+    #[test]
+    fn test_root_detection_strategies() {
+        let dir = create_project(&[("inner/pyproject.toml", ""), ("inner/pkg/mod.py", "")]);
+        fs::remove_file(dir.path().join("pyproject.toml")).unwrap();
+        fs::create_dir(dir.path().join(".git")).unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let target = root.join("inner").join("pkg");
+
+        for (args, expected_root) in [
+            (vec![], root.join("inner")),
+            (vec!["--root-detection", "markers"], root.join("inner")),
+            (vec!["--root-detection", "git"], root.clone()),
+            (vec!["--root-detection", "none"], target.clone()),
+        ] {
+            let cli = Cli::parse_from(
+                ["undead", target.to_str().unwrap()]
+                    .into_iter()
+                    .chain(args.iter().copied()),
+            );
+            let walked = walk_project(&cli, &WalkMonitor::default()).unwrap();
+            assert_eq!(walked.import_roots[0], expected_root, "{args:?}");
+        }
+    }
+
+    #[test]
+    fn test_modules_root_sets_dotted_names() {
+        let dir = create_project(&[
+            ("main.py", "import app.utils\n"),
+            ("src/app/__init__.py", ""),
+            ("src/app/utils.py", ""),
+            ("src/app/orphan.py", ""),
+        ]);
+        let modules_root = dir.path().join("src");
+        let report = find_dead_files(&cli_for(
+            &dir,
+            &["--modules-root", modules_root.to_str().unwrap()],
+        ))
+        .unwrap();
+        assert_eq!(dead_reprs(&report), vec!["app/orphan.py", "main.py"]);
+
+        let report = find_dead_files(&cli_for(&dir, &[])).unwrap();
+        assert_eq!(
+            dead_reprs(&report),
+            vec!["main.py", "src/app/orphan.py", "src/app/utils.py"]
+        );
+    }
+
+    #[test]
+    fn test_setup_py_declarations() {
+        let dir = create_project(&[
+            (
+                "setup.py",
+                "from setuptools import setup\n\nsetup(py_modules=[\"tool\"], packages=[\"app\"])\n",
+            ),
+            ("tool.py", ""),
+            ("orphan.py", ""),
+            ("app/__init__.py", ""),
+            ("app/api.py", ""),
+        ]);
+        let report = find_dead_files(&cli_for(&dir, &[])).unwrap();
+        assert_eq!(
+            dead_reprs(&report),
+            vec!["app/api.py", "orphan.py", "setup.py"]
+        );
+        let confidences: Vec<Confidence> = report.dead_files.iter().map(|f| f.confidence).collect();
+        assert_eq!(
+            confidences,
+            vec![Confidence::Medium, Confidence::High, Confidence::High]
+        );
+    }
+
+    #[test]
+    fn test_exclude_tests_from_imports() {
+        let dir = create_project(&[
+            ("main.py", "import app.core\n"),
+            ("app/__init__.py", ""),
+            ("app/core.py", ""),
+            ("app/fixtures.py", ""),
+            (
+                "tests/test_core.py",
+                "import app.core\nimport app.fixtures\n",
+            ),
+            ("app/helpers_test.py", "import app.fixtures\n"),
+        ]);
+        let test_core = format!("tests{MAIN_SEPARATOR_STR}test_core.py");
+        let helpers_test = format!("app{MAIN_SEPARATOR_STR}helpers_test.py");
+
+        let report = find_dead_files(&cli_for(&dir, &[])).unwrap();
+        assert_eq!(
+            dead_reprs(&report),
+            vec![
+                helpers_test.clone(),
+                "main.py".to_string(),
+                test_core.clone()
+            ]
+        );
+
+        let report = find_dead_files(&cli_for(&dir, &["--exclude-tests-from-imports"])).unwrap();
+        assert_eq!(
+            dead_reprs(&report),
+            vec![
+                format!("app{MAIN_SEPARATOR_STR}fixtures.py"),
+                helpers_test,
+                "main.py".to_string(),
+                test_core,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_console_scripts_are_used() {
+        let dir = create_project(&[
+            (
+                "pyproject.toml",
+                "[project]\nname = \"demo\"\n\n[project.scripts]\ndemo = \"demo.cli:main\"\n",
+            ),
+            ("demo/__init__.py", ""),
+            ("demo/cli.py", ""),
+            ("demo/unused.py", ""),
+        ]);
+        let report = find_dead_files(&cli_for(&dir, &[])).unwrap();
+        assert_eq!(
+            dead_reprs(&report),
+            vec![format!("demo{MAIN_SEPARATOR_STR}unused.py")]
+        );
+
+        let dir = create_project(&[
+            (
+                "setup.py",
+                "from setuptools import setup\n\nsetup(\n    entry_points={\"console_scripts\": [\"tool = tool:main\"]},\n)\n",
+            ),
+            ("tool.py", ""),
+            ("unused.py", ""),
+        ]);
+        let report = find_dead_files(&cli_for(&dir, &[])).unwrap();
+        assert_eq!(dead_reprs(&report), vec!["setup.py", "unused.py"]);
+    }
+
+    #[test]
+    fn test_pr_mode_only_checks_changed_files() {
+        let dir = create_project(&[
+            ("main.py", "import used\n"),
+            ("used.py", ""),
+            ("old.py", ""),
+        ]);
+        run_git(&dir, &["init", "-q"], None);
+        run_git(&dir, &["add", "."], None);
+        run_git(&dir, &["commit", "-q", "-m", "initial"], None);
+        let count_candidates = |args: &[&str]| {
+            let mut candidates = 0;
+            scan_candidates(&cli_for(&dir, args), |_, _| candidates += 1).unwrap();
+            candidates
+        };
+
+        assert_eq!(count_candidates(&[]), 3);
+        assert_eq!(count_candidates(&["--pr-mode", "HEAD"]), 0);
+
+        fs::write(dir.path().join("new.py"), "").unwrap();
+        assert_eq!(count_candidates(&["--pr-mode", "HEAD"]), 1);
+        let report = find_dead_files(&cli_for(&dir, &["--pr-mode", "HEAD"])).unwrap();
+        assert_eq!(dead_reprs(&report), vec!["new.py"]);
+    }
+
+    #[test]
+    fn test_pr_mode_reuses_the_import_cache() {
+        let dir = create_project(&[
+            ("main.py", "import used\n"),
+            ("used.py", ""),
+            ("old.py", ""),
+        ]);
+        run_git(&dir, &["init", "-q"], None);
+        run_git(&dir, &["add", "."], None);
+        run_git(&dir, &["commit", "-q", "-m", "initial"], None);
+        let root = dir.path().canonicalize().unwrap();
+        let cache_dir = tempfile::tempdir().unwrap();
+        let pr_mode = cli_for(
+            &dir,
+            &[
+                "--pr-mode",
+                "HEAD",
+                "--cache-dir",
+                cache_dir.path().to_str().unwrap(),
+            ],
+        );
+
+        assert!(find_dead_files(&pr_mode).unwrap().skipped.is_empty());
+        // an unparseable main.py only gets noticed by parsing it again
+        rewrite_unnoticed(&dir.path().join("main.py"), "def broken(\n");
+        assert!(find_dead_files(&pr_mode).unwrap().skipped.is_empty());
+        let report = find_dead_files(&cli_for(&dir, &[])).unwrap();
+        assert_eq!(report.skipped.len(), 1);
+
+        // pull request checks cache imports without being asked to
+        assert!(cache_file_path(&cli_for(&dir, &[]), &root).is_none());
+        assert!(cache_file_path(&cli_for(&dir, &["--pr-mode", "HEAD"]), &root).is_some());
+    }
+}
@@ -1,386 +1,1073 @@
-use crate::cli::Cli;
-use clap::Parser;
-use crossbeam::queue::SegQueue;
-use grep::{
-    matcher::Matcher,
-    regex::RegexMatcher,
-    searcher::{sinks::UTF8, Searcher},
-};
-use ignore::{types::TypesBuilder, DirEntry, WalkBuilder};
-
-use crate::printer::Printer;
-use rayon::prelude::*;
-use rustpython_ast::{Mod, ModModule, Stmt, StmtImport, StmtImportFrom, Visitor};
-use rustpython_parser::{parse, Mode};
-use std::time::Instant;
-use std::{
-    collections::HashSet,
-    path::{Path, PathBuf, MAIN_SEPARATOR_STR},
+use clap::ValueEnum;
+use std::io::{BufRead, IsTerminal};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use termcolor::{ColorChoice, StandardStream, WriteColor};
+use undead::cli::{Cli, Command, Format, PathStyle};
+use undead::printer::{self, Printer};
+use undead::{
+    diff, find_candidates, find_dead_files_streaming, find_entrypoints, find_import_roots,
+    find_tree_stats, DeadFile, Report, UndeadError,
 };
 
-mod cli;
-mod printer;
+/// Exit code when interrupted by ctrl-c, following the shell convention of 128 + SIGINT.
+const INTERRUPTED_EXIT_CODE: i32 = 130;
+/// Exit code when dead files were found and `--exit-code` is set.
+const DEAD_FILES_EXIT_CODE: i32 = 1;
+
+/// Colors are left out when stdout isn't a terminal, or when asked to through `NO_COLOR`.
+fn color_choice() -> ColorChoice {
+    if printer::no_color() {
+        ColorChoice::Never
+    } else {
+        ColorChoice::Auto
+    }
+}
+
+/// Dead files found so far, to print them even if the search gets interrupted.
+///
+/// Dead files are only known once every file has been parsed, so an interruption while parsing
+/// leaves none to print.
+#[derive(Default)]
+struct PartialResults {
+    dead_files: Mutex<Vec<DeadFile>>,
+}
+
+impl PartialResults {
+    fn push(&self, dead_file: DeadFile) {
+        self.dead_files.lock().unwrap().push(dead_file);
+    }
+
+    fn take(&self) -> Vec<DeadFile> {
+        std::mem::take(&mut *self.dead_files.lock().unwrap())
+    }
+
+    /// Prints the dead files found so far as the report would, followed by a note on
+    /// `note_stream` that the search was cut short.
+    fn flush(
+        &self,
+        cli: &Cli,
+        duration: Duration,
+        stream: &mut dyn WriteColor,
+        summary_stream: &mut dyn WriteColor,
+        note_stream: &mut dyn WriteColor,
+    ) -> anyhow::Result<()> {
+        let mut report = Report::new(self.take(), Default::default());
+        report.sort(cli.sort);
+        print_report(&report, cli, stream, summary_stream, duration)?;
+        let printer = printer::TerminalPrinter {
+            output_encoding: cli.output_encoding,
+        };
+        printer.print(
+            printer::Printable::Message("(interrupted)".to_string()),
+            note_stream,
+        )?;
+        Ok(())
+    }
+}
 
-pub fn main() -> anyhow::Result<()> {
+pub fn main() {
     let start = Instant::now();
-    let cli = Cli::parse();
+    let cli = Cli::parse_command_line();
+    if let Err(error) = run(cli, start) {
+        let mut stderr = StandardStream::stderr(color_choice());
+        let exit_code = report_failure(&error, &mut stderr).unwrap_or(1);
+        std::process::exit(exit_code);
+    }
+}
 
-    let target_paths = resolve_paths(cli.paths);
-    let ignore_paths = cli.ignore_paths;
+/// Sizes the thread pool after `--jobs`, leaving it to one thread per core by default.
+fn configure_threads(cli: &Cli) -> anyhow::Result<()> {
+    if let Some(jobs) = cli.jobs {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(jobs.get())
+            .build_global()?;
+    }
+    Ok(())
+}
 
-    let target_paths = parallel_build_path_iterator(&target_paths, &ignore_paths)?;
-    let python_root = find_python_project_root(&target_paths[0]).unwrap();
+/// Prints why `run` failed, returning the exit code to use: finding nothing to analyze isn't an
+/// error.
+fn report_failure(error: &anyhow::Error, stream: &mut dyn WriteColor) -> std::io::Result<i32> {
+    let printer = printer::TerminalPrinter::default();
+    if matches!(
+        error.downcast_ref::<UndeadError>(),
+        Some(UndeadError::NoPythonFiles)
+    ) {
+        printer.print(printer::Printable::Message(error.to_string()), stream)?;
+        return Ok(0);
+    }
+    printer.print(
+        printer::Printable::Error(format!("error: {error:#}")),
+        stream,
+    )?;
+    Ok(1)
+}
 
-    let no_entrypoint_paths = target_paths.clone().into_par_iter().filter(|path| {
-        if let Some(file_name) = path.file_name() {
-            if file_name.to_string_lossy().to_string() == PYTHON_INIT_FILE {
-                return false;
-            }
-        }
-        return !file_contains_name_equals_main(path).unwrap();
-    });
+/// Does what the command line asks for, leaving failures for `main` to report.
+fn run(mut cli: Cli, start: Instant) -> anyhow::Result<()> {
+    configure_threads(&cli)?;
+    if cli.stdin {
+        cli.paths.extend(read_paths(&mut std::io::stdin().lock())?);
+    }
+    if let Some(Command::Diff { old, new }) = &cli.command {
+        return print_diff(&cli, old, new);
+    }
 
-    let all_paths = parallel_build_path_iterator(&vec![python_root.to_path_buf()], &Vec::new())?;
-    let imports = resolve_imports(compile_imports(&all_paths, &python_root)?);
+    if cli.verify {
+        return print_verification(&cli);
+    }
 
-    let imports_hash_set: HashSet<String> = imports.iter().cloned().collect();
+    if cli.list_entrypoints {
+        return print_entrypoints(&cli);
+    }
 
-    let potentially_dead_modules = no_entrypoint_paths
-        .map(|path| render_as_import_string(&path, python_root))
-        .collect::<Vec<String>>();
+    if cli.list_candidates {
+        return print_candidates(&cli);
+    }
 
-    let mut dead_files = potentially_dead_modules
-        .into_par_iter()
-        .filter(|module| !imports_hash_set.contains(module))
-        .map(|module| module.replace(".", MAIN_SEPARATOR_STR) + PYTHON_EXTENSION)
-        .collect::<Vec<String>>();
-    dead_files.sort();
+    if cli.print_tree_stats {
+        return print_tree_stats(&cli);
+    }
 
-    let printer = printer::TerminalPrinter {};
-    let mut stream = termcolor::StandardStream::stdout(termcolor::ColorChoice::Auto);
-    printer.print(printer::Printable::Separator, &mut stream)?;
-    for dead_file in dead_files.iter() {
-        printer.print(
-            printer::Printable::DeadFile(printer::DeadFile {
-                repr: dead_file,
-                full_path: python_root.join(dead_file).to_str().unwrap(),
-            }),
-            &mut stream,
+    if cli.print_roots {
+        return print_roots(&cli, &mut StandardStream::stdout(color_choice()));
+    }
+
+    if let Some(target) = &cli.explain {
+        return print_explanation(&cli, target);
+    }
+
+    let cli = Arc::new(cli);
+    let partial_results = Arc::new(PartialResults::default());
+    let interrupted_results = Arc::clone(&partial_results);
+    let interrupted_cli = Arc::clone(&cli);
+    ctrlc::set_handler(move || {
+        let _ = interrupted_results.flush(
+            &interrupted_cli,
+            start.elapsed(),
+            &mut StandardStream::stdout(color_choice()),
+            &mut summary_stream(&interrupted_cli),
+            &mut StandardStream::stderr(color_choice()),
+        );
+        std::process::exit(INTERRUPTED_EXIT_CODE);
+    })?;
+    let summary = find_dead_files_streaming(&cli, |dead_file| partial_results.push(dead_file))?;
+    let mut report = Report::new(partial_results.take(), summary);
+    report.sort(cli.sort);
+
+    let mut stdout = StandardStream::stdout(color_choice());
+    let mut summary_stream = summary_stream(&cli);
+    let duration = start.elapsed();
+    print_report(&report, &cli, &mut stdout, &mut summary_stream, duration)?;
+    if cli.summary_json {
+        write_summary_json(&report, duration, &mut std::io::stderr())?;
+    }
+    if let Some(baseline) = &cli.write_baseline {
+        undead::baseline::write_baseline(baseline, &report.dead_files)?;
+    }
+    if let Some(out_dir) = &cli.out_dir {
+        write_report_files(&report, &cli.formats, out_dir, duration)?;
+    }
+    if cli.annotate {
+        annotate_dead_files(&report, &cli)?;
+    }
+    if cli.fix {
+        fix_dead_files(
+            &report,
+            &cli,
+            |question| confirm(question, &cli),
+            &mut StandardStream::stderr(color_choice()),
         )?;
     }
-    printer.print(printer::Printable::Separator, &mut stream)?;
-    printer.print(
-        printer::Printable::Stats(printer::Stats {
-            scanned_files: &all_paths.len(),
-            dead_files: &dead_files.len(),
-            duration: start.elapsed(),
-        }),
-        &mut stream,
-    )?;
+    check_parse_errors_ratio(&report, &cli)?;
+    if fails_on_dead_files(&report, &cli) {
+        std::process::exit(DEAD_FILES_EXIT_CODE);
+    }
     Ok(())
 }
 
-fn resolve_paths(paths: Vec<PathBuf>) -> Vec<PathBuf> {
-    paths
-        .into_iter()
-        .map(|path| path.canonicalize().unwrap())
-        .collect()
+/// Where the decorations of the report go, stdout unless `--stats-to-stderr` says otherwise.
+fn summary_stream(cli: &Cli) -> StandardStream {
+    if cli.stats_to_stderr {
+        StandardStream::stderr(color_choice())
+    } else {
+        StandardStream::stdout(color_choice())
+    }
 }
 
-static PYTHON_INIT_FILE: &str = "__init__.py";
-static PYTHON_EXTENSION: &str = ".py";
-
-fn resolve_imports(imports: Vec<Import>) -> Vec<String> {
-    let mut resolved_imports = vec![];
-    for import in imports {
-        match import {
-            Import::Module(module) => resolved_imports.push(module),
-            Import::Package(mut package) => {
-                package.push_str(PYTHON_INIT_FILE);
-                resolved_imports.push(package);
-            }
+/// Reads one path per line, skipping blank lines.
+fn read_paths(input: &mut dyn BufRead) -> std::io::Result<Vec<std::path::PathBuf>> {
+    let mut paths = Vec::new();
+    for line in input.lines() {
+        let line = line?;
+        let line = line.trim();
+        if !line.is_empty() {
+            paths.push(line.into());
         }
     }
-    resolved_imports
+    Ok(paths)
 }
 
-fn compile_imports(python_files: &Vec<PathBuf>, python_root: &Path) -> anyhow::Result<Vec<Import>> {
-    let imports_queue = SegQueue::<Import>::new();
-    python_files
-        .par_iter()
-        .map(|path| match extract_imports(&path, &python_root) {
-            Ok(imports) => {
-                imports
-                    .into_iter()
-                    .for_each(|import| imports_queue.push(import));
-                Ok(())
-            }
-            Err(_) => return Err(()),
-        })
-        .collect::<Vec<_>>();
-
-    Ok(imports_queue.into_iter().collect())
+/// Asks on the terminal before modifying files, unless `--yes` already said so.
+fn confirm(question: &str, cli: &Cli) -> anyhow::Result<bool> {
+    if cli.yes {
+        return Ok(true);
+    }
+    let stdin = std::io::stdin();
+    if !stdin.is_terminal() {
+        anyhow::bail!(
+            "modifying files needs confirmation, pass --yes when not running in a terminal"
+        );
+    }
+    Ok(ask(question, &mut stdin.lock(), &mut std::io::stderr())?)
 }
 
-#[derive(Debug, PartialEq, Clone)]
-enum Import {
-    Module(String),
-    Package(String),
+/// Prompts `question` on `stream`, telling whether the answer read from `answers` is yes.
+fn ask(
+    question: &str,
+    answers: &mut dyn BufRead,
+    stream: &mut dyn std::io::Write,
+) -> std::io::Result<bool> {
+    write!(stream, "{question} [y/N] ")?;
+    stream.flush()?;
+    let mut answer = String::new();
+    answers.read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
 }
-impl Import {
-    fn from_import(import: &StmtImport, python_root: &Path) -> Vec<Import> {
-        import
-            .names
-            .iter()
-            .map(|alias| {
-                let alias_name = alias.name.to_string();
-                let full_path = python_root.join(alias_name.replace(".", MAIN_SEPARATOR_STR));
-                if full_path.is_dir() {
-                    Import::Package(alias_name)
-                } else {
-                    Import::Module(alias_name)
-                }
-            })
-            .collect()
-    }
-
-    fn from_import_from(
-        import_from: &StmtImportFrom,
-        current_file_path: &Path,
-        python_root: &Path,
-    ) -> Vec<Import> {
-        let mut base_import_path: PathBuf;
-        match import_from.level {
-            Some(level) => {
-                // absolute import
-                if level.to_usize() == 0 {
-                    base_import_path = python_root.to_path_buf();
-                // relative import
-                } else {
-                    base_import_path = current_file_path.to_path_buf();
-                    for _ in 0..level.to_usize() {
-                        base_import_path = base_import_path.parent().unwrap().to_path_buf();
-                    }
-                }
-            }
-            // when does this happen?
-            None => {
-                base_import_path = python_root.to_path_buf();
-            }
-        }
-        let mut full_import_path: PathBuf = base_import_path;
-        if let Some(module) = import_from.module.as_ref() {
-            full_import_path =
-                full_import_path.join(module.to_string().replace(".", MAIN_SEPARATOR_STR));
-            if !full_import_path.is_dir() {
-                return vec![Import::Module(render_as_import_string(
-                    &full_import_path,
-                    python_root,
-                ))];
-            }
+
+/// Marks each dead file with `annotate::MARKER`, once confirmed.
+fn annotate_dead_files(report: &Report, cli: &Cli) -> anyhow::Result<()> {
+    if report.dead_files.is_empty()
+        || !confirm(&format!("Annotate {} files?", report.dead_files.len()), cli)?
+    {
+        return Ok(());
+    }
+    let mut annotated = 0;
+    for dead_file in report.dead_files.iter() {
+        if undead::annotate::annotate_file(&dead_file.full_path)? {
+            annotated += 1;
         }
-        import_from
-            .names
-            .iter()
-            .map(|alias| {
-                let alias_name = alias.name.to_string();
-                let final_import_path = full_import_path.join(alias_name);
-                let final_import = render_as_import_string(&final_import_path, python_root);
-                if final_import_path.is_dir() {
-                    Import::Package(final_import)
-                } else {
-                    Import::Module(final_import)
-                }
-            })
-            .collect()
     }
+    let printer = printer::TerminalPrinter {
+        output_encoding: cli.output_encoding,
+    };
+    printer.print(
+        printer::Printable::Message(format!("annotated {annotated} dead files")),
+        &mut StandardStream::stderr(color_choice()),
+    )?;
+    Ok(())
+}
+
+/// Deletes the dead files once `confirm` agrees to, sparing package `__init__.py` files whose
+/// removal would break the imports of their siblings.
+fn fix_dead_files(
+    report: &Report,
+    cli: &Cli,
+    confirm: impl FnOnce(&str) -> anyhow::Result<bool>,
+    stream: &mut dyn WriteColor,
+) -> anyhow::Result<()> {
+    let deletable: Vec<&DeadFile> = report
+        .dead_files
+        .iter()
+        .filter(|dead_file| !dead_file.full_path.ends_with("__init__.py"))
+        .collect();
+    if deletable.is_empty() || !confirm(&format!("Delete {} files?", deletable.len()))? {
+        return Ok(());
+    }
+    for dead_file in deletable.iter() {
+        std::fs::remove_file(&dead_file.full_path)?;
+    }
+    let printer = printer::TerminalPrinter {
+        output_encoding: cli.output_encoding,
+    };
+    printer.print(
+        printer::Printable::Message(format!("deleted {} dead files", deletable.len())),
+        stream,
+    )?;
+    Ok(())
 }
 
-fn render_as_import_string(path: &Path, python_root: &Path) -> String {
-    let mut prefix = python_root.to_string_lossy().to_string();
-    prefix.push_str(MAIN_SEPARATOR_STR);
-    let mut result = path.to_string_lossy().to_string();
-    result = result.strip_prefix(&prefix).unwrap_or(&result).to_string();
-    result = result
-        .strip_suffix(PYTHON_EXTENSION)
-        .unwrap_or(&result)
-        .to_string();
-    result.to_string().replace(MAIN_SEPARATOR_STR, ".")
+/// Tells whether to exit with `DEAD_FILES_EXIT_CODE`, for CI checks.
+fn fails_on_dead_files(report: &Report, cli: &Cli) -> bool {
+    cli.exit_code && !report.dead_files.is_empty()
 }
 
-fn extract_imports(path: &Path, python_root: &Path) -> anyhow::Result<Vec<Import>> {
-    let file_contents = std::fs::read_to_string(path)?;
-    match parse(&file_contents, Mode::Module, "<embedded>") {
-        Ok(Mod::Module(ModModule {
-            range: _,
-            body,
-            type_ignores: __,
-        })) => {
-            let mut visitor = ImportVisitor {
-                imports: vec![],
-                python_root: python_root.to_path_buf(),
-                current_file_path: path.to_path_buf(),
-            };
-            // it seems rustpython's asts don't implement accept
-            body.iter()
-                .for_each(|stmt| visitor.visit_stmt(stmt.clone()));
-            Ok(visitor.imports)
+/// Fails when too many files couldn't be parsed for the results to be trusted, which usually
+/// means the project targets an unsupported python version.
+fn check_parse_errors_ratio(report: &Report, cli: &Cli) -> anyhow::Result<()> {
+    if let Some(max_ratio) = cli.fail_on_parse_errors_ratio {
+        let ratio = report.parse_errors_ratio();
+        if ratio > max_ratio {
+            anyhow::bail!(
+                "{} out of {} files failed to parse ({:.2}), above the allowed ratio of {:.2}",
+                report.skipped.len(),
+                report.scanned_files,
+                ratio,
+                max_ratio
+            );
         }
-        _ => Err(anyhow::anyhow!("Error parsing file: {:?}", path)),
     }
+    Ok(())
 }
 
-#[derive(Debug, Clone)]
-struct ImportVisitor {
-    pub imports: Vec<Import>,
-    pub python_root: PathBuf,
-    pub current_file_path: PathBuf,
+fn print_diff(cli: &Cli, old: &Path, new: &Path) -> anyhow::Result<()> {
+    let printer = printer::TerminalPrinter {
+        output_encoding: cli.output_encoding,
+    };
+    let mut stdout = StandardStream::stdout(color_choice());
+    let diff = diff::diff_reports(&diff::read_report(old)?, &diff::read_report(new)?);
+    for dead_file in diff.newly_dead.iter() {
+        printer.print(
+            printer::Printable::Message(format!("+ {}", dead_file.repr)),
+            &mut stdout,
+        )?;
+    }
+    for dead_file in diff.resolved.iter() {
+        printer.print(
+            printer::Printable::Message(format!("- {}", dead_file.repr)),
+            &mut stdout,
+        )?;
+    }
+    printer.print(printer::Printable::Separator, &mut stdout)?;
+    printer.print(
+        printer::Printable::Message(format!(
+            "{} newly dead, {} resolved, {:+} dead files ({:+} lines)",
+            diff.newly_dead.len(),
+            diff.resolved.len(),
+            diff.count_change,
+            diff.lines_change
+        )),
+        &mut stdout,
+    )?;
+    Ok(())
 }
 
-impl Visitor for ImportVisitor {
-    fn visit_stmt_import(&mut self, stmt: StmtImport) {
-        self.imports
-            .extend(Import::from_import(&stmt, &self.python_root));
+fn print_entrypoints(cli: &Cli) -> anyhow::Result<()> {
+    let printer = printer::TerminalPrinter {
+        output_encoding: cli.output_encoding,
+    };
+    let mut stdout = StandardStream::stdout(color_choice());
+    for entrypoint in find_entrypoints(cli)? {
+        printer.print(
+            printer::Printable::Entrypoint(printer::Entrypoint {
+                repr: &entrypoint.repr,
+                full_path: entrypoint.full_path.to_str().unwrap(),
+                reason: entrypoint.reason.to_string(),
+            }),
+            &mut stdout,
+        )?;
     }
+    Ok(())
+}
 
-    fn visit_stmt_import_from(&mut self, stmt: StmtImportFrom) {
-        self.imports.extend(Import::from_import_from(
-            &stmt,
-            &self.current_file_path,
-            &self.python_root,
-        ));
+fn print_candidates(cli: &Cli) -> anyhow::Result<()> {
+    let printer = printer::TerminalPrinter {
+        output_encoding: cli.output_encoding,
+    };
+    let mut stdout = StandardStream::stdout(color_choice());
+    for candidate in find_candidates(cli)? {
+        printer.print(
+            printer::Printable::DeadFile(printer::DeadFile {
+                repr: &candidate.repr,
+                full_path: candidate.full_path.to_str().unwrap(),
+            }),
+            &mut stdout,
+        )?;
     }
+    Ok(())
 }
 
-fn parallel_build_path_iterator(
-    paths: &Vec<PathBuf>,
-    ignore_paths: &Vec<PathBuf>,
-) -> anyhow::Result<Vec<PathBuf>> {
-    let walk_builder = walk_builder(paths, ignore_paths);
-    let file_queue = SegQueue::<PathBuf>::new();
-    walk_builder.build_parallel().run(|| {
-        Box::new(
-            |entry: Result<DirEntry, ignore::Error>| -> ignore::WalkState {
-                match entry {
-                    Ok(entry) => {
-                        let file_type = entry.file_type().unwrap();
-                        if !file_type.is_dir() {
-                            file_queue.push(entry.path().to_path_buf());
-                        }
-                        ignore::WalkState::Continue
-                    }
-                    Err(err) => {
-                        eprintln!("Error: {}", err);
-                        ignore::WalkState::Continue
-                    }
-                }
-            },
-        )
-    });
-    Ok(file_queue.into_iter().collect())
+/// Prints one `<kind>\t<path>` line per import root, or a JSON array of them.
+fn print_roots(cli: &Cli, stream: &mut dyn WriteColor) -> anyhow::Result<()> {
+    let import_roots = find_import_roots(cli)?;
+    if cli.format == Format::Json {
+        serde_json::to_writer_pretty(&mut *stream, &import_roots)?;
+        writeln!(stream)?;
+        return Ok(());
+    }
+    let printer = printer::TerminalPrinter {
+        output_encoding: cli.output_encoding,
+    };
+    for import_root in import_roots {
+        printer.print(
+            printer::Printable::Message(format!(
+                "{}\t{}",
+                import_root.kind,
+                import_root.path.display()
+            )),
+            stream,
+        )?;
+    }
+    Ok(())
 }
 
-fn walk_builder(paths: &[PathBuf], ignore_paths: &[PathBuf]) -> WalkBuilder {
-    let mut types_builder = TypesBuilder::new();
-    types_builder.add_defaults().select("python");
+fn print_tree_stats(cli: &Cli) -> anyhow::Result<()> {
+    let printer = printer::TerminalPrinter {
+        output_encoding: cli.output_encoding,
+    };
+    let mut stdout = StandardStream::stdout(color_choice());
+    for directory in find_tree_stats(cli)? {
+        printer.print(
+            printer::Printable::Message(format!(
+                "{}: {} dead / {} total",
+                directory.repr, directory.dead_files, directory.total_files
+            )),
+            &mut stdout,
+        )?;
+    }
+    Ok(())
+}
 
-    let mut walk_builder = WalkBuilder::new(&paths[0]);
-    for path in paths.iter().skip(1) {
-        walk_builder.add(path);
+fn print_explanation(cli: &Cli, target: &str) -> anyhow::Result<()> {
+    let printer = printer::TerminalPrinter {
+        output_encoding: cli.output_encoding,
+    };
+    let mut stdout = StandardStream::stdout(color_choice());
+    let explanation = undead::explain(cli, target)?;
+    let mut lines = vec![
+        format!("module: {}", explanation.module),
+        match &explanation.full_path {
+            Some(full_path) => format!("file: {}", full_path.display()),
+            None => "file: not found in the project".to_string(),
+        },
+        format!("used: {}", if explanation.is_used { "yes" } else { "no" }),
+    ];
+    if explanation.importers.is_empty() {
+        lines.push("imported by: nothing".to_string());
     }
-    let ignored_paths = ignore_paths.to_vec();
-    walk_builder.filter_entry(move |entry| {
-        for ignore in ignored_paths.iter() {
-            if entry.path().ends_with(ignore) {
-                return false;
-            }
-        }
-        true
-    });
-    walk_builder.types(types_builder.build().unwrap());
-    walk_builder
+    lines.extend(
+        explanation
+            .importers
+            .iter()
+            .map(|importer| format!("imported by: {importer}")),
+    );
+    for line in lines {
+        printer.print(printer::Printable::Message(line), &mut stdout)?;
+    }
+    Ok(())
 }
 
-fn file_contains_name_equals_main(path: &PathBuf) -> anyhow::Result<bool> {
-    let matcher = RegexMatcher::new(r#"if\s+__name__\s*==\s*["']__main__["']:"#).unwrap();
-    let mut matches = vec![];
-    Searcher::new().search_path(
-        &matcher,
-        path,
-        UTF8(|lnum, line| match matcher.find(line.as_bytes()) {
-            Ok(Some(_)) => {
-                matches.push((lnum, line.to_string()));
-                return Ok(true);
-            }
-            Ok(None) => return Ok(false),
-            Err(err) => return Err(err.into()),
-        }),
-    )?;
-    if matches.is_empty() {
-        return Ok(false);
+fn print_verification(cli: &Cli) -> anyhow::Result<()> {
+    let printer = printer::TerminalPrinter {
+        output_encoding: cli.output_encoding,
+    };
+    let mut stdout = StandardStream::stdout(color_choice());
+    match undead::verify::run() {
+        Ok(()) => {
+            printer.print(printer::Printable::Message("OK".to_string()), &mut stdout)?;
+            Ok(())
+        }
+        Err(error) => {
+            printer.print(
+                printer::Printable::Error(format!("FAIL: {error}")),
+                &mut stdout,
+            )?;
+            std::process::exit(1)
+        }
     }
-    Ok(true)
 }
 
-fn is_python_project_root(dir: &Path) -> bool {
-    let markers = vec!["setup.py", "pyproject.toml", ".git"];
-    for marker in markers {
-        if dir.join(marker).exists() {
-            return true;
+/// Writes the whole report to `stream` in `format`, if it is a document format rather than a
+/// terminal one, telling whether it did.
+fn write_document(
+    report: &Report,
+    format: Format,
+    duration: Duration,
+    stream: &mut dyn std::io::Write,
+) -> anyhow::Result<bool> {
+    match format {
+        Format::Json => {
+            let printer = printer::JsonPrinter::new(report)?;
+            printer.print(
+                printer::Printable::Stats(printer::Stats {
+                    scanned_files: &report.scanned_files,
+                    dead_files: &report.dead_files.len(),
+                    parse_failures: &report.skipped.len(),
+                    duration,
+                }),
+                &mut termcolor::NoColor::new(stream),
+            )?;
         }
+        Format::Html => undead::html::write_report(report, duration, stream)?,
+        Format::Markdown => undead::markdown::write_report(report, duration, stream)?,
+        Format::Csv => undead::csv::write_report(report, stream)?,
+        Format::Sarif => undead::sarif::write_report(report, stream)?,
+        Format::Pretty | Format::HumanTree => return Ok(false),
     }
-    false
+    Ok(true)
 }
 
-/// Finds the root path of a Python project starting from a given directory.
-fn find_python_project_root(start_dir: &Path) -> Option<&Path> {
-    let mut current_dir = start_dir;
+/// Writes the report to `out_dir` once per format, as `report.<extension>`.
+fn write_report_files(
+    report: &Report,
+    formats: &[Format],
+    out_dir: &Path,
+    duration: Duration,
+) -> anyhow::Result<()> {
+    std::fs::create_dir_all(out_dir)?;
+    for format in formats {
+        let Some(extension) = format.file_extension() else {
+            anyhow::bail!(
+                "--formats only takes formats writing a whole document, not `{}`",
+                format.to_possible_value().unwrap().get_name()
+            );
+        };
+        let path = out_dir.join(format!("report.{extension}"));
+        let mut file = std::io::BufWriter::new(std::fs::File::create(&path)?);
+        write_document(report, *format, duration, &mut file)?;
+        std::io::Write::flush(&mut file)?;
+    }
+    Ok(())
+}
 
-    loop {
-        if is_python_project_root(current_dir) {
-            return Some(current_dir);
+/// Prints dead files to `stream` and the decorations (separators and stats) to `summary_stream`.
+fn print_report(
+    report: &Report,
+    cli: &Cli,
+    stream: &mut dyn WriteColor,
+    summary_stream: &mut dyn WriteColor,
+    duration: Duration,
+) -> anyhow::Result<()> {
+    if cli.count {
+        writeln!(stream, "{}", report.dead_files.len())?;
+        return Ok(());
+    }
+    if write_document(report, cli.format, duration, stream)? {
+        return Ok(());
+    }
+    let printer = printer::TerminalPrinter {
+        output_encoding: cli.output_encoding,
+    };
+    let current_dir = std::env::current_dir()?;
+    let display_name = |dead_file: &undead::DeadFile| {
+        if cli.output_modules {
+            dead_file.module()
+        } else {
+            display_path(dead_file, cli.path_style, &current_dir)
         }
-
-        // Move to the parent directory
-        match current_dir.parent() {
-            Some(parent) => current_dir = parent,
-            None => break,
+    };
+    let dead_file_names: Vec<String> = report.dead_files.iter().map(display_name).collect();
+    let dead_files =
+        report
+            .dead_files
+            .iter()
+            .zip(dead_file_names.iter())
+            .map(|(dead_file, name)| printer::DeadFile {
+                repr: name,
+                full_path: dead_file.full_path.to_str().unwrap(),
+            });
+    printer.print(printer::Printable::Separator, summary_stream)?;
+    match cli.format {
+        Format::Pretty => {
+            for dead_file in dead_files {
+                printer.print(printer::Printable::DeadFile(dead_file), stream)?;
+            }
+        }
+        Format::HumanTree => {
+            printer.print(printer::Printable::Tree(dead_files.collect()), stream)?;
+        }
+        Format::Json | Format::Html | Format::Markdown | Format::Csv | Format::Sarif => {
+            unreachable!("these reports are printed as a whole")
+        }
+    }
+    if !report.near_dead_files.is_empty() {
+        printer.print(printer::Printable::Separator, summary_stream)?;
+        for near_dead in report.near_dead_files.iter() {
+            printer.print(
+                printer::Printable::NearDeadFile(printer::NearDeadFile {
+                    file: printer::DeadFile {
+                        repr: &display_name(&near_dead.file),
+                        full_path: near_dead.file.full_path.to_str().unwrap(),
+                    },
+                    importers: near_dead.importers,
+                }),
+                stream,
+            )?;
+        }
+    }
+    if !report.duplicates.is_empty() {
+        printer.print(printer::Printable::Separator, summary_stream)?;
+        for group in report.duplicates.iter() {
+            printer.print(
+                printer::Printable::Message(format!("identical files: {}", group.join(", "))),
+                stream,
+            )?;
         }
     }
+    printer.print(printer::Printable::Separator, summary_stream)?;
+    for warning in report.warnings.iter() {
+        printer.print(printer::Printable::Warning(warning.clone()), summary_stream)?;
+    }
+    for skipped in report.skipped.iter() {
+        printer.print(
+            printer::Printable::Warning(format!(
+                "skipped {}: {}",
+                skipped.full_path.display(),
+                skipped.reason
+            )),
+            summary_stream,
+        )?;
+    }
+    printer.print(
+        printer::Printable::Stats(printer::Stats {
+            scanned_files: &report.scanned_files,
+            dead_files: &report.dead_files.len(),
+            parse_failures: &report.skipped.len(),
+            duration,
+        }),
+        summary_stream,
+    )?;
+    Ok(())
+}
 
-    None
+/// The path of `dead_file` as `style` asks for, relative paths being relative to `current_dir`.
+fn display_path(dead_file: &DeadFile, style: PathStyle, current_dir: &Path) -> String {
+    match style {
+        PathStyle::Module => dead_file.repr.clone(),
+        PathStyle::Relative => undead::relative_path(&dead_file.full_path, current_dir)
+            .display()
+            .to_string(),
+        PathStyle::Absolute => dead_file.full_path.display().to_string(),
+    }
+}
+
+/// Writes the stats of `report` as a single line JSON object, for CI jobs to parse.
+fn write_summary_json(
+    report: &Report,
+    duration: Duration,
+    stream: &mut dyn std::io::Write,
+) -> anyhow::Result<()> {
+    let summary = serde_json::json!({
+        "scanned_files": report.scanned_files,
+        "dead_files": report.dead_files.len(),
+        "near_dead_files": report.near_dead_files.len(),
+        "skipped_files": report.skipped.len(),
+        "warnings": report.warnings.len(),
+        "duration_ms": duration.as_millis(),
+    });
+    serde_json::to_writer(&mut *stream, &summary)?;
+    writeln!(stream)?;
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use clap::Parser;
+    use std::path::PathBuf;
+    use undead::{find_dead_files, Confidence, Summary};
+
+    /// A file of `/project` found dead with high confidence.
+    fn dead_file(repr: &str) -> DeadFile {
+        DeadFile {
+            repr: repr.to_string(),
+            full_path: Path::new("/project").join(repr),
+            confidence: Confidence::High,
+            relative_path: Default::default(),
+        }
+    }
+
+    /// A report of `scanned_files` files of `/project`, the dead ones being `reprs`.
+    fn report(reprs: &[&str], scanned_files: usize) -> Report {
+        Report::new(
+            reprs.iter().map(|repr| dead_file(repr)).collect(),
+            Summary {
+                scanned_files,
+                ..Default::default()
+            },
+        )
+    }
 
     #[test]
-    fn test_from_import_from() {
-        let current_file_path = Path::new("/e/f/g/h.py");
-        let python_root = Path::new("/e/f");
-        match parse("from a.b import c, d", Mode::Module, "<embedded>") {
-            Ok(Mod::Module(ModModule {
-                range: _,
-                body,
-                type_ignores: _,
-            })) => {
-                let imports: Vec<Import> = body
-                    .iter()
-                    .map(|stmt| match stmt {
-                        Stmt::Import(import) => Import::from_import(import, python_root),
-                        Stmt::ImportFrom(import_from) => {
-                            Import::from_import_from(import_from, current_file_path, python_root)
-                        }
-                        _ => vec![],
-                    })
-                    .flatten()
-                    .collect();
-                assert_eq!(imports.len(), 2);
-                assert_eq!(imports[0], Import::Module("a.b.c".to_string()));
-                assert_eq!(imports[1], Import::Module("a.b.d".to_string()));
-            }
-            _ => assert!(false),
+    fn test_stats_to_stderr_keeps_stdout_clean() {
+        let report = report(&["a.py", "b.py"], 2);
+
+        let mut stdout = termcolor::NoColor::new(vec![]);
+        let mut stderr = termcolor::NoColor::new(vec![]);
+        let cli = Cli::parse_from(["undead", "/project", "--stats-to-stderr"]);
+        print_report(&report, &cli, &mut stdout, &mut stderr, Duration::ZERO).unwrap();
+
+        let stdout = String::from_utf8(stdout.into_inner()).unwrap();
+        let lines: Vec<&str> = stdout.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].contains("a.py"));
+        assert!(lines[1].contains("b.py"));
+        assert!(!String::from_utf8(stderr.into_inner()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_json_report_lists_skipped_files() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("pyproject.toml"), "").unwrap();
+        std::fs::write(dir.path().join("main.py"), "import used\n").unwrap();
+        std::fs::write(dir.path().join("used.py"), "").unwrap();
+        std::fs::write(dir.path().join("broken.py"), "def broken(:\n").unwrap();
+        let cli = Cli::parse_from(["undead", dir.path().to_str().unwrap(), "--format", "json"]);
+        let report = find_dead_files(&cli).unwrap();
+
+        let mut stdout = termcolor::NoColor::new(vec![]);
+        let mut stderr = termcolor::NoColor::new(vec![]);
+        print_report(&report, &cli, &mut stdout, &mut stderr, Duration::ZERO).unwrap();
+
+        let json: serde_json::Value = serde_json::from_slice(&stdout.into_inner()).unwrap();
+        let dead_files: Vec<&str> = json["dead_files"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|dead_file| dead_file["repr"].as_str().unwrap())
+            .collect();
+        assert_eq!(dead_files, vec!["main.py"]);
+        let skipped = json["skipped"].as_array().unwrap();
+        assert_eq!(skipped.len(), 1);
+        assert_eq!(skipped[0]["repr"], "broken.py");
+        assert!(skipped[0]["reason"]
+            .as_str()
+            .unwrap()
+            .starts_with("invalid syntax"));
+    }
+
+    #[test]
+    fn test_stats_count_parse_failures() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("pyproject.toml"), "").unwrap();
+        std::fs::write(dir.path().join("legacy.py"), "print \"python 2\"\n").unwrap();
+        std::fs::write(dir.path().join("orphan.py"), "").unwrap();
+        let cli = Cli::parse_from(["undead", dir.path().to_str().unwrap()]);
+        let report = find_dead_files(&cli).unwrap();
+
+        let mut stdout = termcolor::NoColor::new(vec![]);
+        let mut stderr = termcolor::NoColor::new(vec![]);
+        print_report(&report, &cli, &mut stdout, &mut stderr, Duration::ZERO).unwrap();
+        let output = String::from_utf8(stderr.into_inner()).unwrap()
+            + &String::from_utf8(stdout.into_inner()).unwrap();
+        assert!(output.contains("parse_failures: 1"), "{output}");
+    }
+
+    #[test]
+    fn test_fix_deletes_dead_files_once_confirmed() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("pyproject.toml"), "").unwrap();
+        std::fs::write(dir.path().join("orphan.py"), "").unwrap();
+        std::fs::create_dir(dir.path().join("pkg")).unwrap();
+        std::fs::write(dir.path().join("pkg/__init__.py"), "").unwrap();
+        let cli = Cli::parse_from([
+            "undead",
+            dir.path().to_str().unwrap(),
+            "--fix",
+            "--include-init",
+        ]);
+        let report = find_dead_files(&cli).unwrap();
+        assert_eq!(report.dead_files.len(), 2);
+
+        let mut stream = termcolor::NoColor::new(vec![]);
+        let mut answer = ask("Delete 1 files?", &mut &b"n\n"[..], &mut vec![]);
+        fix_dead_files(&report, &cli, |_| Ok(answer?), &mut stream).unwrap();
+        assert!(dir.path().join("orphan.py").exists());
+
+        answer = ask("Delete 1 files?", &mut &b"y\n"[..], &mut vec![]);
+        fix_dead_files(&report, &cli, |_| Ok(answer?), &mut stream).unwrap();
+        assert!(!dir.path().join("orphan.py").exists());
+        assert!(dir.path().join("pkg/__init__.py").exists());
+        let output = String::from_utf8(stream.into_inner()).unwrap();
+        assert_eq!(output, "deleted 1 dead files\n");
+    }
+
+    #[test]
+    fn test_print_roots_lists_configured_roots() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        std::fs::write(root.join("pyproject.toml"), "").unwrap();
+        std::fs::create_dir_all(root.join("src/pkg")).unwrap();
+        std::fs::write(root.join("src/pkg/__init__.py"), "").unwrap();
+        let namespace_root = root.join("src");
+        let cli = Cli::parse_from([
+            "undead",
+            root.to_str().unwrap(),
+            "--print-roots",
+            "--namespace-root",
+            namespace_root.to_str().unwrap(),
+            "--format",
+            "json",
+        ]);
+
+        let mut stream = termcolor::NoColor::new(vec![]);
+        print_roots(&cli, &mut stream).unwrap();
+        let roots: serde_json::Value = serde_json::from_slice(&stream.into_inner()).unwrap();
+        assert_eq!(
+            roots,
+            serde_json::json!([
+                {"path": root, "kind": "project-root"},
+                {"path": namespace_root, "kind": "namespace-root"},
+            ])
+        );
+    }
+
+    #[test]
+    fn test_paths_read_from_stdin_are_the_only_candidates() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        std::fs::write(root.join("pyproject.toml"), "").unwrap();
+        for file in ["changed.py", "also_changed.py", "untouched.py"] {
+            std::fs::write(root.join(file), "").unwrap();
+        }
+        let input = format!(
+            "{}\n\n{}\n",
+            root.join("changed.py").display(),
+            root.join("also_changed.py").display()
+        );
+        let mut cli = Cli::parse_from(["undead", "--stdin"]);
+        cli.paths.extend(read_paths(&mut input.as_bytes()).unwrap());
+
+        let candidates: Vec<String> = undead::find_candidates(&cli)
+            .unwrap()
+            .into_iter()
+            .map(|candidate| candidate.repr)
+            .collect();
+        assert_eq!(candidates, vec!["also_changed.py", "changed.py"]);
+    }
+
+    #[test]
+    fn test_json_report_round_trips() {
+        let mut report = report(&["a/b.py"], 120);
+        report.dead_files[0].confidence = Confidence::Medium;
+        report.warnings.push("a warning".to_string());
+        let cli = Cli::parse_from(["undead", "/project", "--format", "json"]);
+        let mut stdout = termcolor::NoColor::new(vec![]);
+        let mut stderr = termcolor::NoColor::new(vec![]);
+        print_report(&report, &cli, &mut stdout, &mut stderr, Duration::ZERO).unwrap();
+
+        // a single document on stdout, nothing else
+        assert!(stderr.into_inner().is_empty());
+        let parsed: Report = serde_json::from_slice(&stdout.into_inner()).unwrap();
+        assert_eq!(parsed.dead_files, report.dead_files);
+        assert_eq!(parsed.scanned_files, 120);
+        assert_eq!(parsed.warnings, vec!["a warning"]);
+    }
+
+    #[test]
+    fn test_exit_code_only_fails_on_dead_files() {
+        let mut report = report(&[], 1);
+        let cli = Cli::parse_from(["undead", "/project", "--exit-code"]);
+        assert!(!fails_on_dead_files(&report, &cli));
+
+        report.dead_files.push(dead_file("a.py"));
+        assert!(fails_on_dead_files(&report, &cli));
+        assert!(!fails_on_dead_files(
+            &report,
+            &Cli::parse_from(["undead", "/project"])
+        ));
+    }
+
+    #[test]
+    fn test_write_report_files() {
+        let report = report(&["a.py"], 2);
+        let dir = tempfile::tempdir().unwrap();
+        let out_dir = dir.path().join("reports");
+        write_report_files(
+            &report,
+            &[Format::Json, Format::Csv],
+            &out_dir,
+            Duration::ZERO,
+        )
+        .unwrap();
+
+        let mut files: Vec<String> = std::fs::read_dir(&out_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        files.sort();
+        assert_eq!(files, vec!["report.csv", "report.json"]);
+        let json: Report =
+            serde_json::from_str(&std::fs::read_to_string(out_dir.join("report.json")).unwrap())
+                .unwrap();
+        assert_eq!(json.dead_files, report.dead_files);
+        let csv = std::fs::read_to_string(out_dir.join("report.csv")).unwrap();
+        assert_eq!(csv.lines().count(), 1 + report.dead_files.len());
+        assert!(csv.contains("a.py"));
+
+        assert!(write_report_files(&report, &[Format::Pretty], &out_dir, Duration::ZERO).is_err());
+    }
+
+    #[test]
+    fn test_no_python_files_is_not_a_failure() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("README.md"), "").unwrap();
+        let cli = Cli::parse_from(["undead", dir.path().to_str().unwrap()]);
+        let error = anyhow::Error::from(find_dead_files(&cli).unwrap_err());
+
+        let mut stream = termcolor::NoColor::new(vec![]);
+        assert_eq!(report_failure(&error, &mut stream).unwrap(), 0);
+        let output = String::from_utf8(stream.into_inner()).unwrap();
+        assert_eq!(output, "no Python files found under the given paths\n");
+    }
+
+    #[test]
+    fn test_summary_json_alongside_csv() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("pyproject.toml"), "").unwrap();
+        std::fs::write(dir.path().join("main.py"), "import used\n").unwrap();
+        std::fs::write(dir.path().join("used.py"), "").unwrap();
+        let cli = Cli::parse_from([
+            "undead",
+            dir.path().to_str().unwrap(),
+            "--format",
+            "csv",
+            "--summary-json",
+        ]);
+        let report = find_dead_files(&cli).unwrap();
+
+        let mut stdout = termcolor::NoColor::new(vec![]);
+        let mut stderr = termcolor::NoColor::new(vec![]);
+        print_report(&report, &cli, &mut stdout, &mut stderr, Duration::ZERO).unwrap();
+        write_summary_json(&report, Duration::ZERO, &mut stderr).unwrap();
+
+        let stdout = String::from_utf8(stdout.into_inner()).unwrap();
+        assert_eq!(stdout, "path,module,confidence\nmain.py,main,high\n");
+        let summary: serde_json::Value = serde_json::from_slice(&stderr.into_inner()).unwrap();
+        assert_eq!(summary["scanned_files"], 2);
+        assert_eq!(summary["dead_files"], 1);
+        assert_eq!(summary["skipped_files"], 0);
+    }
+
+    #[test]
+    fn test_fail_on_parse_errors_ratio() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("pyproject.toml"), "").unwrap();
+        for (name, contents) in [
+            ("a.py", ""),
+            ("b.py", ""),
+            ("c.py", ""),
+            ("broken.py", "def broken(:\n"),
+        ] {
+            std::fs::write(dir.path().join(name), contents).unwrap();
+        }
+        let cli_with_ratio = |ratio: &str| {
+            Cli::parse_from([
+                "undead",
+                dir.path().to_str().unwrap(),
+                "--fail-on-parse-errors-ratio",
+                ratio,
+            ])
         };
+        let report = find_dead_files(&cli_with_ratio("0")).unwrap();
+        assert_eq!(report.parse_errors_ratio(), 0.25);
+
+        assert!(check_parse_errors_ratio(&report, &cli_with_ratio("0.2")).is_err());
+        assert!(check_parse_errors_ratio(&report, &cli_with_ratio("0.25")).is_ok());
+        assert!(check_parse_errors_ratio(&report, &cli_with_ratio("0.5")).is_ok());
+        assert!(
+            Cli::try_parse_from(["undead", ".", "--fail-on-parse-errors-ratio", "1.5"]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_output_modules_prints_dotted_names() {
+        let report = report(
+            &[&["app", "utils", "helpers.py"].join(std::path::MAIN_SEPARATOR_STR)],
+            1,
+        );
+
+        let mut stdout = termcolor::NoColor::new(vec![]);
+        let mut stderr = termcolor::NoColor::new(vec![]);
+        let cli = Cli::parse_from([
+            "undead",
+            "/project",
+            "--output-modules",
+            "--stats-to-stderr",
+        ]);
+        print_report(&report, &cli, &mut stdout, &mut stderr, Duration::ZERO).unwrap();
+
+        let stdout = String::from_utf8(stdout.into_inner()).unwrap();
+        assert_eq!(
+            stdout.lines().collect::<Vec<_>>(),
+            vec!["app.utils.helpers"]
+        );
+    }
+
+    #[test]
+    fn test_count_prints_only_the_number_of_dead_files() {
+        let mut report = report(&["a.py", "b.py"], 3);
+        report.warnings.push("something odd".to_string());
+
+        let mut stdout = termcolor::NoColor::new(vec![]);
+        let mut stderr = termcolor::NoColor::new(vec![]);
+        let cli = Cli::parse_from(["undead", "/project", "--count", "--exit-code"]);
+        print_report(&report, &cli, &mut stdout, &mut stderr, Duration::ZERO).unwrap();
+
+        assert_eq!(String::from_utf8(stdout.into_inner()).unwrap(), "2\n");
+        assert!(stderr.into_inner().is_empty());
+        assert!(fails_on_dead_files(&report, &cli));
+    }
+
+    #[test]
+    fn test_path_styles() {
+        let dead_file = DeadFile {
+            repr: "helpers.py".to_string(),
+            ..dead_file("src/app/helpers.py")
+        };
+        let current_dir = Path::new("/project/docs");
+        let displayed = |style| display_path(&dead_file, style, current_dir);
+
+        assert_eq!(displayed(PathStyle::Module), "helpers.py");
+        assert_eq!(
+            PathBuf::from(displayed(PathStyle::Relative)),
+            ["..", "src", "app", "helpers.py"]
+                .iter()
+                .collect::<PathBuf>()
+        );
+        assert_eq!(
+            PathBuf::from(displayed(PathStyle::Absolute)),
+            PathBuf::from("/project/src/app/helpers.py")
+        );
+    }
+
+    #[test]
+    fn test_interruption_flushes_partial_results() {
+        let partial_results = PartialResults::default();
+        for repr in ["b.py", "a.py"] {
+            partial_results.push(dead_file(repr));
+        }
+
+        let cli = Cli::parse_from(["undead", "--format", "json", "--output-modules"]);
+        let mut stdout = termcolor::NoColor::new(vec![]);
+        let mut stderr = termcolor::NoColor::new(vec![]);
+        partial_results
+            .flush(
+                &cli,
+                Duration::ZERO,
+                &mut stdout,
+                &mut termcolor::NoColor::new(vec![]),
+                &mut stderr,
+            )
+            .unwrap();
+        let report: serde_json::Value = serde_json::from_slice(&stdout.into_inner()).unwrap();
+        let modules: Vec<&str> = report["dead_files"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|dead_file| dead_file["module"].as_str().unwrap())
+            .collect();
+        assert_eq!(modules, vec!["a", "b"]);
+        assert_eq!(
+            String::from_utf8(stderr.into_inner()).unwrap(),
+            "(interrupted)\n"
+        );
+        assert!(partial_results.take().is_empty());
+
+        partial_results.push(dead_file("pkg/c.py"));
+        let cli = Cli::parse_from(["undead", "--output-modules"]);
+        let mut stdout = termcolor::NoColor::new(vec![]);
+        partial_results
+            .flush(
+                &cli,
+                Duration::ZERO,
+                &mut stdout,
+                &mut termcolor::NoColor::new(vec![]),
+                &mut termcolor::NoColor::new(vec![]),
+            )
+            .unwrap();
+        assert_eq!(String::from_utf8(stdout.into_inner()).unwrap(), "pkg.c\n");
     }
 }
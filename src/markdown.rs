@@ -0,0 +1,66 @@
+//! Rendering of reports as Markdown, e.g. for bots posting them as pull request comments.
+use std::io::Write;
+use std::time::Duration;
+
+use crate::printer::file_uri;
+use crate::{count_lines, Report};
+
+/// Writes `report` as a Markdown table of the dead files followed by a summary line.
+pub fn write_report(
+    report: &Report,
+    duration: Duration,
+    stream: &mut dyn Write,
+) -> std::io::Result<()> {
+    writeln!(stream, "| Module | Path | Lines |")?;
+    writeln!(stream, "| --- | --- | ---: |")?;
+    for dead_file in report.dead_files.iter() {
+        writeln!(
+            stream,
+            "| `{}` | [{}](<{}>) | {} |",
+            escape(&dead_file.module()),
+            escape(&dead_file.repr),
+            file_uri(&dead_file.full_path.to_string_lossy()),
+            count_lines(&dead_file.full_path)
+                .map_or_else(|| "?".to_string(), |lines| lines.to_string()),
+        )?;
+    }
+    writeln!(stream)?;
+    writeln!(
+        stream,
+        "**{}** dead files out of {} scanned in {:?}.",
+        report.dead_files.len(),
+        report.scanned_files,
+        duration
+    )
+}
+
+/// Keeps cell contents from breaking out of the table or of the link syntax.
+fn escape(text: &str) -> String {
+    text.replace('|', "\\|")
+        .replace('[', "\\[")
+        .replace(']', "\\]")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rows_link_files_and_escape_pipes() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.py"), "import os\n").unwrap();
+        let report = Report::for_test(dir.path(), &["a.py", "b|c.py"], 4);
+
+        let mut markdown = vec![];
+        write_report(&report, Duration::ZERO, &mut markdown).unwrap();
+        let markdown = String::from_utf8(markdown).unwrap();
+        let rows: Vec<&str> = markdown
+            .lines()
+            .filter(|line| line.starts_with("| `"))
+            .collect();
+
+        assert!(rows[0].starts_with("| `a` | [a.py](<file://"));
+        assert!(rows[0].ends_with("| 1 |"));
+        assert!(rows[1].contains("[b\\|c.py]"));
+    }
+}
@@ -1,81 +1,164 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
 use std::fmt;
-use std::io::Write;
+use std::path::MAIN_SEPARATOR;
 use std::{io::IsTerminal, time::Duration};
-use termcolor::{Color, ColorSpec, StandardStream, WriteColor};
+use termcolor::{Color, ColorSpec, WriteColor};
 use terminal_size::{terminal_size, Width};
 
+use crate::cli::OutputEncoding;
+
 pub enum Printable<'a> {
     Message(String),
     Error(String),
+    Warning(String),
     Stats(Stats<'a>),
     DeadFile(DeadFile<'a>),
+    NearDeadFile(NearDeadFile<'a>),
+    Entrypoint(Entrypoint<'a>),
+    Tree(Vec<DeadFile<'a>>),
     Separator,
 }
 
 const DEFAULT_SEPARATOR: &str = "-";
 const DEFAULT_SEPARATOR_SIZE: u16 = 80;
+/// widest separator printed, whatever the terminal reports
+const MAX_SEPARATOR_SIZE: u16 = 200;
 
 pub trait Printer {
-    fn print(&self, printable: Printable, stream: &mut StandardStream) -> std::io::Result<()> {
-        if is_terminal() {
+    fn output_encoding(&self) -> OutputEncoding;
+
+    fn print(&self, printable: Printable, stream: &mut dyn WriteColor) -> std::io::Result<()> {
+        self.print_to(printable, stream, is_terminal())
+    }
+
+    /// Prints decorated output (colors, hyperlinks) only when the output goes to a terminal.
+    fn print_to(
+        &self,
+        printable: Printable,
+        stream: &mut dyn WriteColor,
+        is_terminal: bool,
+    ) -> std::io::Result<()> {
+        if is_terminal {
             self.print_generic(&printable, stream)
         } else {
             match printable {
-                Printable::Message(msg) => println!("{}", msg),
-                Printable::Error(err) => eprintln!("{}", err),
-                Printable::Stats(stats) => println!("{:?}", stats),
-                Printable::DeadFile(file) => println!("{}", file.repr),
+                Printable::Message(msg) => writeln!(stream, "{}", msg),
+                Printable::Error(err) => {
+                    eprintln!("{}", err);
+                    Ok(())
+                }
+                Printable::Warning(warning) => writeln!(stream, "warning: {}", warning),
+                Printable::Stats(stats) => writeln!(stream, "{:?}", stats),
+                Printable::DeadFile(file) => writeln!(stream, "{}", file.repr),
+                Printable::NearDeadFile(near_dead) => writeln!(
+                    stream,
+                    "{} ({})",
+                    near_dead.file.repr,
+                    importers_label(near_dead.importers)
+                ),
+                Printable::Entrypoint(entrypoint) => {
+                    writeln!(stream, "{} ({})", entrypoint.repr, entrypoint.reason)
+                }
+                Printable::Tree(files) => {
+                    for line in tree_lines(&files, self.output_encoding()) {
+                        writeln!(stream, "{}{}", line.prefix, line.name)?;
+                    }
+                    Ok(())
+                }
                 Printable::Separator => {
-                    println!(
+                    writeln!(
+                        stream,
                         "\n{}\n",
                         DEFAULT_SEPARATOR.repeat(DEFAULT_SEPARATOR_SIZE as usize)
                     )
                 }
-            };
-            Ok(())
+            }
         }
     }
     fn print_generic(
         &self,
         printable: &Printable,
-        stream: &mut StandardStream,
+        stream: &mut dyn WriteColor,
     ) -> std::io::Result<()> {
         match printable {
             Printable::Message(msg) => self.print_message(msg, stream),
             Printable::Error(err) => self.print_error(err, stream),
+            Printable::Warning(warning) => self.print_warning(warning, stream),
             Printable::Stats(stats) => self.print_stats(stats, stream),
             Printable::DeadFile(file) => self.print_dead_file(file, stream),
+            Printable::NearDeadFile(near_dead) => self.print_near_dead_file(near_dead, stream),
+            Printable::Entrypoint(entrypoint) => self.print_entrypoint(entrypoint, stream),
+            Printable::Tree(files) => self.print_tree(files, stream),
             Printable::Separator => self.print_separator(stream),
         }
     }
 
-    fn print_message(&self, msg: &str, stream: &mut StandardStream) -> std::io::Result<()>;
-    fn print_error(&self, err: &str, stream: &mut StandardStream) -> std::io::Result<()>;
-    fn print_stats(&self, stats: &Stats, stream: &mut StandardStream) -> std::io::Result<()>;
-    fn print_dead_file(&self, file: &DeadFile, stream: &mut StandardStream) -> std::io::Result<()>;
-    fn print_separator(&self, stream: &mut StandardStream) -> std::io::Result<()>;
+    fn print_message(&self, msg: &str, stream: &mut dyn WriteColor) -> std::io::Result<()>;
+    fn print_error(&self, err: &str, stream: &mut dyn WriteColor) -> std::io::Result<()>;
+    fn print_warning(&self, warning: &str, stream: &mut dyn WriteColor) -> std::io::Result<()>;
+    fn print_stats(&self, stats: &Stats, stream: &mut dyn WriteColor) -> std::io::Result<()>;
+    fn print_dead_file(&self, file: &DeadFile, stream: &mut dyn WriteColor) -> std::io::Result<()>;
+    fn print_near_dead_file(
+        &self,
+        near_dead: &NearDeadFile,
+        stream: &mut dyn WriteColor,
+    ) -> std::io::Result<()>;
+    fn print_entrypoint(
+        &self,
+        entrypoint: &Entrypoint,
+        stream: &mut dyn WriteColor,
+    ) -> std::io::Result<()>;
+    fn print_tree(&self, files: &[DeadFile], stream: &mut dyn WriteColor) -> std::io::Result<()>;
+    fn print_separator(&self, stream: &mut dyn WriteColor) -> std::io::Result<()>;
 }
 
+/// Whether colors were disabled through a non-empty `NO_COLOR`, see https://no-color.org.
+pub fn no_color() -> bool {
+    is_no_color(std::env::var_os("NO_COLOR"))
+}
+
+/// Tells whether a value of `NO_COLOR` asks for no colors.
+fn is_no_color(value: Option<std::ffi::OsString>) -> bool {
+    value.is_some_and(|value| !value.is_empty())
+}
+
+/// Whether stdout, where results go, is a terminal.
 fn is_terminal() -> bool {
-    std::io::stdin().is_terminal()
+    std::io::stdout().is_terminal()
 }
 
-pub struct TerminalPrinter;
+#[derive(Default)]
+pub struct TerminalPrinter {
+    pub output_encoding: OutputEncoding,
+}
 
 impl Printer for TerminalPrinter {
-    fn print_message(&self, msg: &str, stream: &mut StandardStream) -> std::io::Result<()> {
+    fn output_encoding(&self) -> OutputEncoding {
+        self.output_encoding
+    }
+
+    fn print_message(&self, msg: &str, stream: &mut dyn WriteColor) -> std::io::Result<()> {
         stream.set_color(ColorSpec::new().set_fg(Some(Color::Cyan)))?;
         writeln!(stream, "{}", msg)
     }
 
-    fn print_error(&self, err: &str, stream: &mut StandardStream) -> std::io::Result<()> {
+    fn print_error(&self, err: &str, stream: &mut dyn WriteColor) -> std::io::Result<()> {
         stream.set_color(ColorSpec::new().set_fg(Some(Color::Red)))?;
         writeln!(stream, "{}", err)
     }
 
-    fn print_stats(&self, stats: &Stats, stream: &mut StandardStream) -> std::io::Result<()> {
+    fn print_warning(&self, warning: &str, stream: &mut dyn WriteColor) -> std::io::Result<()> {
+        stream.set_color(ColorSpec::new().set_fg(Some(Color::Magenta)))?;
+        writeln!(stream, "warning: {}", warning)
+    }
+
+    fn print_stats(&self, stats: &Stats, stream: &mut dyn WriteColor) -> std::io::Result<()> {
         stream.set_color(ColorSpec::new().set_fg(Some(Color::Yellow)))?;
         writeln!(stream, "Found {} dead files", stats.dead_files)?;
+        if *stats.parse_failures > 0 {
+            writeln!(stream, "Failed to parse {} files", stats.parse_failures)?;
+        }
         writeln!(
             stream,
             "Scanned {} files in {:?}",
@@ -83,29 +166,180 @@ impl Printer for TerminalPrinter {
         )
     }
 
-    fn print_dead_file(&self, file: &DeadFile, stream: &mut StandardStream) -> std::io::Result<()> {
+    fn print_dead_file(&self, file: &DeadFile, stream: &mut dyn WriteColor) -> std::io::Result<()> {
         stream.set_color(ColorSpec::new().set_fg(Some(Color::Green)))?;
-        let link = Hyperlink {
-            uri: &format!("file://{}", file.full_path),
-            id: None,
-        };
-        writeln!(stream, "{link}{}{link:#}", file.repr)
+        write_link(stream, file.full_path, file.repr)?;
+        writeln!(stream)
+    }
+
+    fn print_near_dead_file(
+        &self,
+        near_dead: &NearDeadFile,
+        stream: &mut dyn WriteColor,
+    ) -> std::io::Result<()> {
+        stream.set_color(ColorSpec::new().set_fg(Some(Color::Yellow)))?;
+        write_link(stream, near_dead.file.full_path, near_dead.file.repr)?;
+        writeln!(stream, " ({})", importers_label(near_dead.importers))
     }
 
-    fn print_separator(&self, stream: &mut StandardStream) -> std::io::Result<()> {
-        let mut width = DEFAULT_SEPARATOR_SIZE;
-        if let Some((Width(w), _)) = terminal_size() {
-            width = w;
+    fn print_entrypoint(
+        &self,
+        entrypoint: &Entrypoint,
+        stream: &mut dyn WriteColor,
+    ) -> std::io::Result<()> {
+        stream.set_color(ColorSpec::new().set_fg(Some(Color::Blue)))?;
+        write_link(stream, entrypoint.full_path, entrypoint.repr)?;
+        stream.set_color(ColorSpec::new().set_fg(Some(Color::Cyan)))?;
+        writeln!(stream, " ({})", entrypoint.reason)
+    }
+
+    fn print_tree(&self, files: &[DeadFile], stream: &mut dyn WriteColor) -> std::io::Result<()> {
+        for line in tree_lines(files, self.output_encoding) {
+            stream.set_color(ColorSpec::new().set_fg(Some(Color::Cyan)))?;
+            write!(stream, "{}", line.prefix)?;
+            match line.file {
+                Some(file) => {
+                    stream.set_color(ColorSpec::new().set_fg(Some(Color::Green)))?;
+                    write_link(stream, file.full_path, line.name)?;
+                    writeln!(stream)?;
+                }
+                None => writeln!(stream, "{}", line.name)?,
+            }
         }
+        Ok(())
+    }
+
+    fn print_separator(&self, stream: &mut dyn WriteColor) -> std::io::Result<()> {
+        let width = separator_width(terminal_size().map(|(Width(width), _)| width));
         stream.set_color(ColorSpec::new().set_fg(Some(Color::Cyan)))?;
         writeln!(stream, "{}", DEFAULT_SEPARATOR.repeat(width as usize))
     }
 }
 
+/// Writes `text` as a hyperlink to the file at `path`, or as is on streams without colors, which
+/// leave out escape sequences altogether (e.g. with `NO_COLOR`).
+fn write_link(stream: &mut dyn WriteColor, path: &str, text: &str) -> std::io::Result<()> {
+    if !stream.supports_color() {
+        return write!(stream, "{text}");
+    }
+    let link = Hyperlink {
+        uri: &file_uri(path),
+        id: None,
+    };
+    write!(stream, "{link}{text}{link:#}")
+}
+
+/// Picks how wide separators are given the width the terminal reports, if any, falling back to
+/// the default on absurd reports.
+fn separator_width(terminal_width: Option<u16>) -> u16 {
+    match terminal_width {
+        Some(0) | None => DEFAULT_SEPARATOR_SIZE,
+        Some(width) => width.min(MAX_SEPARATOR_SIZE),
+    }
+}
+
+/// Prints a report as a single JSON document: the report itself, as `--baseline` and `diff` read
+/// it back, along with a `stats` object. Printing the stats writes out the document, and nothing
+/// else gets printed besides errors.
+pub struct JsonPrinter {
+    document: RefCell<serde_json::Map<String, serde_json::Value>>,
+}
+
+impl JsonPrinter {
+    pub fn new(report: &crate::Report) -> serde_json::Result<JsonPrinter> {
+        let document = match serde_json::to_value(report)? {
+            serde_json::Value::Object(document) => document,
+            _ => unreachable!("reports serialize as objects"),
+        };
+        Ok(JsonPrinter {
+            document: RefCell::new(document),
+        })
+    }
+}
+
+impl Printer for JsonPrinter {
+    fn output_encoding(&self) -> OutputEncoding {
+        OutputEncoding::Utf8
+    }
+
+    /// Terminals or not, the output is the same document.
+    fn print_to(
+        &self,
+        printable: Printable,
+        stream: &mut dyn WriteColor,
+        _is_terminal: bool,
+    ) -> std::io::Result<()> {
+        self.print_generic(&printable, stream)
+    }
+
+    fn print_message(&self, _msg: &str, _stream: &mut dyn WriteColor) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    fn print_error(&self, err: &str, _stream: &mut dyn WriteColor) -> std::io::Result<()> {
+        eprintln!("{}", err);
+        Ok(())
+    }
+
+    /// Warnings are already part of the report.
+    fn print_warning(&self, _warning: &str, _stream: &mut dyn WriteColor) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    fn print_stats(&self, stats: &Stats, stream: &mut dyn WriteColor) -> std::io::Result<()> {
+        let mut document = self.document.borrow_mut();
+        document.insert(
+            "stats".to_string(),
+            serde_json::json!({
+                "scanned_files": stats.scanned_files,
+                "dead_files": stats.dead_files,
+                "parse_failures": stats.parse_failures,
+                "duration_ms": stats.duration.as_millis(),
+            }),
+        );
+        serde_json::to_writer_pretty(&mut *stream, &*document)?;
+        writeln!(stream)
+    }
+
+    /// Dead files are already part of the report.
+    fn print_dead_file(
+        &self,
+        _file: &DeadFile,
+        _stream: &mut dyn WriteColor,
+    ) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    fn print_near_dead_file(
+        &self,
+        _near_dead: &NearDeadFile,
+        _stream: &mut dyn WriteColor,
+    ) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    fn print_entrypoint(
+        &self,
+        _entrypoint: &Entrypoint,
+        _stream: &mut dyn WriteColor,
+    ) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    fn print_tree(&self, _files: &[DeadFile], _stream: &mut dyn WriteColor) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    fn print_separator(&self, _stream: &mut dyn WriteColor) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
 #[derive(Debug)]
 pub struct Stats<'a> {
     pub dead_files: &'a usize,
     pub scanned_files: &'a usize,
+    pub parse_failures: &'a usize,
     pub duration: Duration,
 }
 
@@ -115,6 +349,115 @@ pub struct DeadFile<'a> {
     pub full_path: &'a str,
 }
 
+#[derive(Debug)]
+pub struct NearDeadFile<'a> {
+    pub file: DeadFile<'a>,
+    pub importers: usize,
+}
+
+#[derive(Debug)]
+pub struct Entrypoint<'a> {
+    pub repr: &'a str,
+    pub full_path: &'a str,
+    pub reason: String,
+}
+
+fn importers_label(importers: usize) -> String {
+    match importers {
+        1 => "near-dead: imported by 1 file".to_string(),
+        n => format!("near-dead: imported by {} files", n),
+    }
+}
+
+/// A single line of a rendered directory tree.
+#[derive(Debug)]
+pub struct TreeLine<'a> {
+    /// box-drawing characters leading up to the name
+    pub prefix: String,
+    pub name: &'a str,
+    /// the dead file this line stands for, `None` for directories
+    pub file: Option<&'a DeadFile<'a>>,
+}
+
+#[derive(Default)]
+struct TreeNode<'a> {
+    file: Option<&'a DeadFile<'a>>,
+    children: BTreeMap<&'a str, TreeNode<'a>>,
+}
+
+struct TreeGlyphs {
+    branch: &'static str,
+    last_branch: &'static str,
+    continuation: &'static str,
+    last_continuation: &'static str,
+}
+
+const UTF8_TREE_GLYPHS: TreeGlyphs = TreeGlyphs {
+    branch: "├── ",
+    last_branch: "└── ",
+    continuation: "│   ",
+    last_continuation: "    ",
+};
+
+const ASCII_TREE_GLYPHS: TreeGlyphs = TreeGlyphs {
+    branch: "|-- ",
+    last_branch: "`-- ",
+    continuation: "|   ",
+    last_continuation: "    ",
+};
+
+/// Lays out dead files as a directory tree (à la `tree`), based on their `repr`.
+pub fn tree_lines<'a>(
+    files: &'a [DeadFile<'a>],
+    output_encoding: OutputEncoding,
+) -> Vec<TreeLine<'a>> {
+    let glyphs = match output_encoding {
+        OutputEncoding::Utf8 => &UTF8_TREE_GLYPHS,
+        OutputEncoding::Ascii => &ASCII_TREE_GLYPHS,
+    };
+    let mut root = TreeNode::default();
+    for file in files {
+        let mut node = &mut root;
+        for component in file.repr.split(MAIN_SEPARATOR) {
+            node = node.children.entry(component).or_default();
+        }
+        node.file = Some(file);
+    }
+
+    let mut lines = vec![];
+    for (name, node) in root.children.iter() {
+        lines.push(TreeLine {
+            prefix: String::new(),
+            name,
+            file: node.file,
+        });
+        push_tree_children(node, "", glyphs, &mut lines);
+    }
+    lines
+}
+
+fn push_tree_children<'a>(
+    node: &TreeNode<'a>,
+    indent: &str,
+    glyphs: &TreeGlyphs,
+    lines: &mut Vec<TreeLine<'a>>,
+) {
+    let last_index = node.children.len().saturating_sub(1);
+    for (i, (name, child)) in node.children.iter().enumerate() {
+        let (branch, continuation) = if i == last_index {
+            (glyphs.last_branch, glyphs.last_continuation)
+        } else {
+            (glyphs.branch, glyphs.continuation)
+        };
+        lines.push(TreeLine {
+            prefix: format!("{indent}{branch}"),
+            name,
+            file: child.file,
+        });
+        push_tree_children(child, &format!("{indent}{continuation}"), glyphs, lines);
+    }
+}
+
 #[derive(Default, Debug, PartialEq, Clone)]
 pub struct Hyperlink<'a> {
     // maybe this should use u8 to support non-utf encodings?
@@ -122,6 +465,26 @@ pub struct Hyperlink<'a> {
     id: Option<&'a str>,
 }
 
+/// Turns a file path into a `file://` URI, percent-encoding (RFC 3986) everything but unreserved
+/// characters and separators so that paths with spaces or `#` still make valid links.
+pub fn file_uri(path: &str) -> String {
+    let mut uri = String::from("file://");
+    let path = path.replace('\\', "/");
+    // windows paths start with a drive letter rather than a separator
+    if !path.starts_with('/') {
+        uri.push('/');
+    }
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' | b':' => {
+                uri.push(byte as char)
+            }
+            _ => uri.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    uri
+}
+
 const OSC8: &str = "\x1b]8";
 
 /// string terminator
@@ -141,3 +504,211 @@ impl fmt::Display for Hyperlink<'_> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tree_test_reprs() -> [String; 4] {
+        [
+            ["app", "models", "user.py"].join(std::path::MAIN_SEPARATOR_STR),
+            ["app", "models", "zone.py"].join(std::path::MAIN_SEPARATOR_STR),
+            ["app", "views.py"].join(std::path::MAIN_SEPARATOR_STR),
+            "script.py".to_string(),
+        ]
+    }
+
+    #[test]
+    fn test_tree_lines() {
+        let reprs = tree_test_reprs();
+        let files: Vec<DeadFile> = reprs
+            .iter()
+            .map(|repr| DeadFile {
+                repr,
+                full_path: repr,
+            })
+            .collect();
+
+        let lines: Vec<String> = tree_lines(&files, OutputEncoding::Utf8)
+            .iter()
+            .map(|line| format!("{}{}", line.prefix, line.name))
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                "app",
+                "├── models",
+                "│   ├── user.py",
+                "│   └── zone.py",
+                "└── views.py",
+                "script.py",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ascii_output_encoding() {
+        let reprs = tree_test_reprs();
+        let files: Vec<DeadFile> = reprs
+            .iter()
+            .map(|repr| DeadFile {
+                repr,
+                full_path: repr,
+            })
+            .collect();
+        let printer = TerminalPrinter {
+            output_encoding: OutputEncoding::Ascii,
+        };
+        let mut stream = termcolor::NoColor::new(vec![]);
+        printer.print(Printable::Separator, &mut stream).unwrap();
+        printer.print(Printable::Tree(files), &mut stream).unwrap();
+
+        let output = stream.into_inner();
+        assert!(output.is_ascii());
+        assert!(String::from_utf8(output).unwrap().contains("`-- views.py"));
+    }
+
+    #[test]
+    fn test_file_uri_percent_encodes_paths() {
+        assert_eq!(file_uri("/project/app.py"), "file:///project/app.py");
+        assert_eq!(
+            file_uri("/my project/#1/café.py"),
+            "file:///my%20project/%231/caf%C3%A9.py"
+        );
+        assert_eq!(
+            file_uri(r"C:\project\a b.py"),
+            "file:///C:/project/a%20b.py"
+        );
+
+        let mut stream = termcolor::Ansi::new(vec![]);
+        TerminalPrinter::default()
+            .print_dead_file(
+                &DeadFile {
+                    repr: "a b.py",
+                    full_path: "/project/a b.py",
+                },
+                &mut stream,
+            )
+            .unwrap();
+        let output = String::from_utf8(stream.into_inner()).unwrap();
+        assert!(output.contains("file:///project/a%20b.py"));
+    }
+
+    #[test]
+    fn test_plain_output_when_not_a_terminal() {
+        let mut stream = termcolor::Ansi::new(vec![]);
+        TerminalPrinter::default()
+            .print_to(
+                Printable::DeadFile(DeadFile {
+                    repr: "a.py",
+                    full_path: "/project/a.py",
+                }),
+                &mut stream,
+                false,
+            )
+            .unwrap();
+        assert_eq!(String::from_utf8(stream.into_inner()).unwrap(), "a.py\n");
+
+        let mut stream = termcolor::Ansi::new(vec![]);
+        TerminalPrinter::default()
+            .print_to(
+                Printable::DeadFile(DeadFile {
+                    repr: "a.py",
+                    full_path: "/project/a.py",
+                }),
+                &mut stream,
+                true,
+            )
+            .unwrap();
+        assert!(stream.into_inner().contains(&0x1b));
+    }
+
+    #[test]
+    fn test_json_printer_writes_a_single_document() {
+        let report = crate::Report::for_test(std::path::Path::new("/root"), &["a/b.py"], 120);
+        let printer = JsonPrinter::new(&report).unwrap();
+        let mut stream = termcolor::NoColor::new(vec![]);
+        printer.print(Printable::Separator, &mut stream).unwrap();
+        printer
+            .print(
+                Printable::DeadFile(DeadFile {
+                    repr: "a/b.py",
+                    full_path: "/root/a/b.py",
+                }),
+                &mut stream,
+            )
+            .unwrap();
+        printer
+            .print(
+                Printable::Stats(Stats {
+                    dead_files: &1,
+                    scanned_files: &120,
+                    parse_failures: &0,
+                    duration: Duration::from_millis(42),
+                }),
+                &mut stream,
+            )
+            .unwrap();
+
+        let document: serde_json::Value = serde_json::from_slice(&stream.into_inner()).unwrap();
+        assert_eq!(document["dead_files"][0]["repr"], "a/b.py");
+        assert_eq!(document["dead_files"][0]["full_path"], "/root/a/b.py");
+        assert_eq!(
+            document["stats"],
+            serde_json::json!({
+                "scanned_files": 120,
+                "dead_files": 1,
+                "parse_failures": 0,
+                "duration_ms": 42,
+            })
+        );
+        // the document is still a report for --baseline and diff
+        let read_back: crate::Report = serde_json::from_value(document).unwrap();
+        assert_eq!(read_back.dead_files, report.dead_files);
+    }
+
+    #[test]
+    fn test_no_color_only_disables_colors() {
+        assert!(is_no_color(Some("1".into())));
+        assert!(!is_no_color(Some("".into())));
+        assert!(!is_no_color(None));
+
+        // terminals without colors get streams with `ColorChoice::Never`, the layout staying
+        let stdout = termcolor::StandardStream::stdout(termcolor::ColorChoice::Never);
+        assert!(!stdout.supports_color());
+        let mut stream = termcolor::NoColor::new(vec![]);
+        let printer = TerminalPrinter::default();
+        printer
+            .print_to(
+                Printable::DeadFile(DeadFile {
+                    repr: "a.py",
+                    full_path: "/project/a.py",
+                }),
+                &mut stream,
+                true,
+            )
+            .unwrap();
+        printer
+            .print_to(
+                Printable::Stats(Stats {
+                    scanned_files: &2,
+                    dead_files: &1,
+                    parse_failures: &0,
+                    duration: Duration::from_millis(5),
+                }),
+                &mut stream,
+                true,
+            )
+            .unwrap();
+        let output = String::from_utf8(stream.into_inner()).unwrap();
+        assert_eq!(output, "a.py\nFound 1 dead files\nScanned 2 files in 5ms\n");
+    }
+
+    #[test]
+    fn test_separator_width_is_bounded() {
+        assert_eq!(separator_width(Some(120)), 120);
+        assert_eq!(separator_width(Some(u16::MAX)), MAX_SEPARATOR_SIZE);
+        assert_eq!(separator_width(Some(0)), DEFAULT_SEPARATOR_SIZE);
+        assert_eq!(separator_width(None), DEFAULT_SEPARATOR_SIZE);
+    }
+}
@@ -0,0 +1,58 @@
+//! Extraction of the console scripts a `pyproject.toml` declares.
+//!
+//! Only the tables listing scripts are read, line by line, which needs no full TOML parser.
+
+/// Tables mapping script names to `module:function` targets.
+static SCRIPT_TABLES: &[&str] = &["project.scripts", "tool.poetry.scripts"];
+
+/// The modules of the `module:function` targets of the scripts declared in `contents`.
+pub(crate) fn script_modules(contents: &str) -> Vec<String> {
+    let mut modules = Vec::new();
+    let mut in_script_table = false;
+    for line in contents.lines() {
+        let line = line.trim();
+        if let Some(table) = line
+            .strip_prefix('[')
+            .and_then(|line| line.strip_suffix(']'))
+        {
+            in_script_table = SCRIPT_TABLES.contains(&table.trim());
+            continue;
+        }
+        if !in_script_table || line.starts_with('#') {
+            continue;
+        }
+        let Some((_, value)) = line.split_once('=') else {
+            continue;
+        };
+        let value = value.trim();
+        let target = value
+            .strip_prefix('"')
+            .and_then(|value| value.strip_suffix('"'))
+            .or_else(|| {
+                value
+                    .strip_prefix('\'')
+                    .and_then(|value| value.strip_suffix('\''))
+            });
+        modules.extend(target.and_then(target_module));
+    }
+    modules
+}
+
+/// The module of an entry point target such as `pkg.cli:main [extra]`.
+pub(crate) fn target_module(target: &str) -> Option<String> {
+    let module = target.split(':').next()?.trim();
+    (!module.is_empty()).then(|| module.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_script_modules() {
+        let modules = script_modules(
+            "[project]\nname = \"demo\"\n\n[project.scripts]\n# the main cli\ndemo = \"demo.cli:main\"\ndemo-admin = 'demo.admin:run'\n\n[tool.poetry.scripts]\nlegacy = \"legacy_tool:main\"\n\n[tool.other]\nkey = \"not.a:script\"\n",
+        );
+        assert_eq!(modules, vec!["demo.cli", "demo.admin", "legacy_tool"]);
+    }
+}
@@ -0,0 +1,91 @@
+//! Rendering of reports as SARIF 2.1.0, for code scanning tools such as GitHub's.
+use std::io::Write;
+
+use serde_json::json;
+
+use crate::printer::file_uri;
+use crate::{Confidence, Report};
+
+/// Rule every dead file is reported under.
+const DEAD_FILE_RULE: &str = "undead/dead-file";
+
+/// Writes `report` as a SARIF log with a single run, each dead file being one result.
+pub fn write_report(report: &Report, stream: &mut dyn Write) -> std::io::Result<()> {
+    let results: Vec<serde_json::Value> = report
+        .dead_files
+        .iter()
+        .map(|dead_file| {
+            json!({
+                "ruleId": DEAD_FILE_RULE,
+                // files dead despite dynamic imports may well be used
+                "level": match dead_file.confidence {
+                    Confidence::High => "warning",
+                    Confidence::Medium | Confidence::Low => "note",
+                },
+                "message": {
+                    "text": format!("`{}` is never imported", dead_file.module()),
+                },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": {
+                            "uri": file_uri(&dead_file.full_path.to_string_lossy()),
+                        },
+                    },
+                }],
+            })
+        })
+        .collect();
+    let log = json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "undead",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": env!("CARGO_PKG_REPOSITORY"),
+                    "rules": [{
+                        "id": DEAD_FILE_RULE,
+                        "shortDescription": {
+                            "text": "Python file that is neither an entrypoint nor imported",
+                        },
+                    }],
+                },
+            },
+            "results": results,
+        }],
+    });
+    serde_json::to_writer_pretty(&mut *stream, &log)?;
+    writeln!(stream)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn test_sarif_required_fields() {
+        let mut report = Report::for_test(Path::new("/project"), &["a.py", "pkg/b.py"], 3);
+        report.dead_files[1].confidence = Confidence::Low;
+
+        let mut sarif = vec![];
+        write_report(&report, &mut sarif).unwrap();
+        let sarif: serde_json::Value = serde_json::from_slice(&sarif).unwrap();
+
+        assert_eq!(sarif["version"], "2.1.0");
+        let run = &sarif["runs"][0];
+        assert_eq!(run["tool"]["driver"]["name"], "undead");
+        assert_eq!(run["tool"]["driver"]["rules"][0]["id"], DEAD_FILE_RULE);
+        let results = run["results"].as_array().unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0]["ruleId"], DEAD_FILE_RULE);
+        assert_eq!(results[0]["level"], "warning");
+        assert_eq!(results[1]["level"], "note");
+        assert_eq!(results[1]["message"]["text"], "`pkg.b` is never imported");
+        assert_eq!(
+            results[1]["locations"][0]["physicalLocation"]["artifactLocation"]["uri"],
+            "file:///project/pkg/b.py"
+        );
+    }
+}
@@ -0,0 +1,168 @@
+//! Extraction of the modules a `setup.py` declares as shipped with the distribution.
+use std::path::Path;
+
+use rustpython_ast::{Constant, Expr, Mod, ModModule, Stmt};
+use rustpython_parser::{parse, Mode};
+
+use crate::pyproject::target_module;
+
+/// The modules and packages a `setup()` call ships.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct SetupDeclarations {
+    /// top-level modules listed in `py_modules`
+    pub(crate) py_modules: Vec<String>,
+    pub(crate) packages: Packages,
+    /// modules of the `console_scripts` and `gui_scripts` entry points
+    pub(crate) entry_point_modules: Vec<String>,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub(crate) enum Packages {
+    #[default]
+    Unspecified,
+    /// dotted package names listed explicitly
+    Listed(Vec<String>),
+    /// `find_packages()`, i.e. every package of the project
+    Found,
+}
+
+impl SetupDeclarations {
+    /// Reads the declarations of the `setup.py` in `project_root`, if there is one that parses.
+    pub(crate) fn load(project_root: &Path) -> SetupDeclarations {
+        std::fs::read_to_string(project_root.join("setup.py"))
+            .ok()
+            .map(|contents| SetupDeclarations::parse(&contents))
+            .unwrap_or_default()
+    }
+
+    fn parse(contents: &str) -> SetupDeclarations {
+        let mut declarations = SetupDeclarations::default();
+        let Ok(Mod::Module(ModModule { body, .. })) = parse(contents, Mode::Module, "setup.py")
+        else {
+            return declarations;
+        };
+        for stmt in body.iter() {
+            let Stmt::Expr(expr) = stmt else {
+                continue;
+            };
+            let Expr::Call(call) = expr.value.as_ref() else {
+                continue;
+            };
+            if !is_named(&call.func, "setup") {
+                continue;
+            }
+            for keyword in call.keywords.iter() {
+                match keyword.arg.as_ref().map(|arg| arg.as_str()) {
+                    Some("py_modules") => declarations.py_modules = string_list(&keyword.value),
+                    Some("entry_points") => {
+                        declarations.entry_point_modules = script_modules(&keyword.value)
+                    }
+                    Some("packages") => {
+                        declarations.packages = match &keyword.value {
+                            Expr::Call(call) if is_named(&call.func, "find_packages") => {
+                                Packages::Found
+                            }
+                            value => Packages::Listed(string_list(value)),
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        declarations
+    }
+
+    /// Tells whether the file at `path`, imported as `module`, belongs to a declared package.
+    pub(crate) fn packages_cover(&self, module: &str, path: &Path) -> bool {
+        match &self.packages {
+            Packages::Unspecified => false,
+            Packages::Listed(packages) => packages.iter().any(|package| {
+                module
+                    .strip_prefix(package.as_str())
+                    .is_some_and(|rest| rest.starts_with('.'))
+            }),
+            Packages::Found => path
+                .parent()
+                .is_some_and(|dir| dir.join("__init__.py").is_file()),
+        }
+    }
+}
+
+/// Matches both `name(...)` and `module.name(...)` calls.
+fn is_named(func: &Expr, name: &str) -> bool {
+    match func {
+        Expr::Name(func) => func.id.as_str() == name,
+        Expr::Attribute(func) => func.attr.as_str() == name,
+        _ => false,
+    }
+}
+
+/// The modules of the `name = module:function` scripts of an `entry_points` dict.
+fn script_modules(entry_points: &Expr) -> Vec<String> {
+    let Expr::Dict(dict) = entry_points else {
+        return vec![];
+    };
+    dict.keys
+        .iter()
+        .zip(dict.values.iter())
+        .filter(|(key, _)| {
+            key.as_ref()
+                .and_then(string_constant)
+                .is_some_and(|group| SCRIPT_GROUPS.contains(&group.as_str()))
+        })
+        .flat_map(|(_, scripts)| string_list(scripts))
+        .filter_map(|script| {
+            let (_, target) = script.split_once('=')?;
+            target_module(target)
+        })
+        .collect()
+}
+
+static SCRIPT_GROUPS: &[&str] = &["console_scripts", "gui_scripts"];
+
+fn string_constant(value: &Expr) -> Option<String> {
+    match value {
+        Expr::Constant(constant) => match &constant.value {
+            Constant::Str(string) => Some(string.clone()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn string_list(value: &Expr) -> Vec<String> {
+    let elements = match value {
+        Expr::List(list) => &list.elts,
+        Expr::Tuple(tuple) => &tuple.elts,
+        _ => return vec![],
+    };
+    elements.iter().filter_map(string_constant).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_setup_declarations() {
+        let declarations = SetupDeclarations::parse(
+            "from setuptools import setup, find_packages\n\nsetup(\n    name=\"demo\",\n    py_modules=[\"tool\", \"helpers\"],\n    packages=find_packages(),\n)\n",
+        );
+        assert_eq!(declarations.py_modules, vec!["tool", "helpers"]);
+        assert_eq!(declarations.packages, Packages::Found);
+
+        let declarations =
+            SetupDeclarations::parse("import setuptools\nsetuptools.setup(packages=[\"app\"])\n");
+        assert_eq!(
+            declarations.packages,
+            Packages::Listed(vec!["app".to_string()])
+        );
+        assert!(declarations.packages_cover("app.utils", Path::new("app/utils.py")));
+        assert!(!declarations.packages_cover("application", Path::new("application.py")));
+
+        let declarations = SetupDeclarations::parse(
+            "setup(\n    entry_points={\n        \"console_scripts\": [\"demo = demo.cli:main\"],\n        \"pytest11\": [\"demo = demo.plugin\"],\n    },\n)\n",
+        );
+        assert_eq!(declarations.entry_point_modules, vec!["demo.cli"]);
+    }
+}
@@ -0,0 +1,68 @@
+//! Self-check running the whole pipeline against a tiny generated project, to confirm the
+//! installation works in the current environment.
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use clap::Parser;
+
+use crate::cli::Cli;
+use crate::find_dead_files;
+
+const FIXTURE: &[(&str, &str)] = &[
+    ("pyproject.toml", ""),
+    (
+        "main.py",
+        "from app import used\n\nif __name__ == \"__main__\":\n    used.run()\n",
+    ),
+    ("app/__init__.py", ""),
+    ("app/used.py", "def run():\n    pass\n"),
+    ("app/dead.py", "def unused():\n    pass\n"),
+];
+
+const EXPECTED_DEAD_FILE: &str = "app/dead.py";
+
+/// Generates the fixture in a temporary directory and checks that exactly its dead file is found.
+pub fn run() -> anyhow::Result<()> {
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH)?.as_nanos();
+    let dir = std::env::temp_dir().join(format!("undead-verify-{}-{nanos}", std::process::id()));
+    let result = write_fixture(&dir).and_then(|_| check_fixture(&dir));
+    let _ = fs::remove_dir_all(&dir);
+    result
+}
+
+fn write_fixture(dir: &Path) -> anyhow::Result<()> {
+    for (path, contents) in FIXTURE {
+        let path = dir.join(path);
+        fs::create_dir_all(path.parent().unwrap())?;
+        fs::write(path, contents)?;
+    }
+    Ok(())
+}
+
+fn check_fixture(dir: &Path) -> anyhow::Result<()> {
+    let cli = Cli::parse_from(["undead".into(), dir.to_path_buf()]);
+    let report = find_dead_files(&cli)?;
+    let dead_files: Vec<String> = report
+        .dead_files
+        .into_iter()
+        .map(|dead_file| dead_file.repr.replace(std::path::MAIN_SEPARATOR, "/"))
+        .collect();
+    if dead_files != [EXPECTED_DEAD_FILE] {
+        anyhow::bail!("expected only {EXPECTED_DEAD_FILE} to be dead, found {dead_files:?}");
+    }
+    if !report.skipped.is_empty() {
+        anyhow::bail!("failed to parse the fixture: {:?}", report.skipped);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_succeeds() {
+        run().unwrap();
+    }
+}
@@ -0,0 +1,68 @@
+//! Runs of the binary, for what depends on the process environment.
+use std::path::Path;
+use std::process::{Command, Output};
+
+fn create_project() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("pyproject.toml"), "").unwrap();
+    std::fs::write(dir.path().join("orphan.py"), "").unwrap();
+    std::fs::write(dir.path().join("unused.py"), "").unwrap();
+    dir
+}
+
+fn undead(dir: &Path, env: &[(&str, &str)], args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_undead"))
+        .arg(dir)
+        .args(args)
+        .envs(env.iter().copied())
+        .env_remove("NO_COLOR")
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_settings_from_environment() {
+    let dir = create_project();
+
+    let output = undead(dir.path(), &[("UNDEAD_FORMAT", "json")], &[]);
+    assert!(output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["dead_files"].as_array().unwrap().len(), 2);
+
+    let output = undead(
+        dir.path(),
+        &[("UNDEAD_FORMAT", "json")],
+        &["--format", "csv"],
+    );
+    assert!(output.status.success());
+    let csv = String::from_utf8(output.stdout).unwrap();
+    assert!(csv.starts_with("path,module,confidence\n"));
+}
+
+#[test]
+fn test_count_wins_over_the_format_from_environment() {
+    let dir = create_project();
+    for format in ["pretty", "json"] {
+        let output = undead(dir.path(), &[("UNDEAD_FORMAT", format)], &["--count"]);
+        assert!(output.status.success(), "{output:?}");
+        assert_eq!(String::from_utf8(output.stdout).unwrap(), "2\n");
+    }
+}
+
+#[test]
+fn test_ignore_globs_from_environment_are_comma_separated() {
+    let dir = create_project();
+    let dead_files = |env: &[(&str, &str)], args: &[&str]| {
+        let output = undead(dir.path(), env, &[&["--format", "csv"], args].concat());
+        assert!(output.status.success(), "{output:?}");
+        String::from_utf8(output.stdout).unwrap().lines().count() - 1
+    };
+
+    assert_eq!(
+        dead_files(&[("UNDEAD_IGNORE", "orphan.py,nothing.py")], &[]),
+        1
+    );
+    // on the command line, commas belong to the glob
+    assert_eq!(dead_files(&[], &["-I", "{orphan,nothing}.py"]), 1);
+    assert_eq!(dead_files(&[], &["-I", "orphan.py,unused.py"]), 2);
+}